@@ -1,88 +1,98 @@
-// #![feature(old_io)]
-// #![feature(libc)]
-//
-// extern crate libc;
-//
-// use std::old_io::stdio::{stdin_raw, stdout_raw};
-// use std::old_io::{MemReader, MemWriter, IoErrorKind};
-// use std::thread;
+extern crate byteorder;
+
+use std::io::{Read, Write, ErrorKind, stdin, stdout};
+use std::collections::HashMap;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+
+// An example vertex for the command operator, whose protocol is described in
+// src/example_shared/operators/command.rs. It echoes each record back at its time, and holds a
+// capability for the least time in its input frontier.
 
 fn main() {
-    // unsafe { libc::fcntl(0, libc::F_SETFL, libc::O_NONBLOCK); }
-    // unsafe { libc::fcntl(1, libc::F_SETFL, libc::O_NONBLOCK); }
-    //
-    // let mut reader = stdin_raw();
-    // let mut writer = stdout_raw();
-    //
-    // let mut memwriter = MemWriter::new();
-    //
-    // memwriter.write_le_u64(1).ok().expect("");
-    // memwriter.write_le_u64(0).ok().expect("");
-    // memwriter.write_le_u64(1).ok().expect("");
-    //
-    // let bytes = memwriter.into_inner();
-    // writer.write_le_u64(bytes.len() as u64).ok().expect("");
-    // writer.write_all(&bytes[..]).ok().expect("");
-    // writer.flush().ok().expect("");
-    //
-    // let mut buffer = Vec::new();
-    //
-    // loop {
-    //     // push some amount, then try decoding...
-    //     match reader.push(1024, &mut buffer) {
-    //         Ok(_)  => { },
-    //         Err(e) => { if e.kind != IoErrorKind::ResourceUnavailable { panic!("Pull error: {}", e) }
-    //                     else { thread::yield_now(); }},
-    //     }
-    //
-    //     let available = buffer.len() as u64;
-    //     let mut reader = MemReader::new(buffer);
-    //     let mut cursor = 0;
-    //
-    //     let mut done = false;
-    //
-    //     while !done {
-    //         let read = match reader.read_le_u64() {
-    //             Ok(x) => x,
-    //             Err(_) => { done = true; 0 }
-    //         };
-    //
-    //         if done || read + 8 + cursor > available {
-    //             done = true;
-    //         }
-    //         else {
-    //             let mut updates = Vec::new();
-    //             let externals = reader.read_le_u64().ok().expect("");
-    //             for _ in (0..externals) {
-    //                 let time = reader.read_le_u64().ok().expect("");
-    //                 let delta= reader.read_le_i64().ok().expect("");
-    //
-    //                 if time < 1000000 {
-    //                     updates.push((time, delta));
-    //                 }
-    //             }
-    //
-    //             if updates.len() > 0 {
-    //                 let mut memwriter = MemWriter::new();
-    //                 memwriter.write_le_u64(updates.len() as u64).ok().expect("");
-    //                 while let Some((time, delta)) = updates.pop() {
-    //                     memwriter.write_le_u64(time).ok().expect("");
-    //                     memwriter.write_le_i64(delta).ok().expect("");
-    //                 }
-    //
-    //                 memwriter.write_le_u64(0).ok().expect("");
-    //                 memwriter.write_le_u64(0).ok().expect("");
-    //
-    //                 let bytes = memwriter.into_inner();
-    //                 writer.write_le_u64(bytes.len() as u64).ok().expect("");
-    //                 writer.write_all(&bytes[..]).ok().expect("");
-    //                 writer.flush().ok().expect("");
-    //             }
-    //
-    //             cursor += 8 + read;
-    //         }
-    //     }
-    //
-    //     buffer = reader.into_inner()[cursor as usize ..available as usize].to_vec();
-    // }
+    let stdin = stdin();
+    let stdout = stdout();
+    let mut reader = stdin.lock();
+    let mut writer = stdout.lock();
+
+    // start with a capability for time zero.
+    let mut held = Some(0u64);
+    write_progress(&mut writer, &[(0, 1)]);
+
+    let mut frontier: HashMap<u64, i64> = HashMap::new();
+
+    while let Some(frame) = read_frame(&mut reader) {
+        match frame[0] {
+            0 => {
+                // data frames have the same body in either direction.
+                write_frame(&mut writer, 0, &frame[1..]);
+            },
+            1 => {
+                let mut slice = &frame[1..];
+                let count = slice.read_u64::<LittleEndian>().unwrap();
+                for _ in (0..count) {
+                    let time = slice.read_u64::<LittleEndian>().unwrap();
+                    let delta = slice.read_i64::<LittleEndian>().unwrap();
+                    let vanished = {
+                        let count = frontier.entry(time).or_insert(0);
+                        *count += delta;
+                        *count == 0
+                    };
+                    if vanished { frontier.remove(&time); }
+                }
+
+                let least = frontier.keys().min().map(|x| *x);
+                if least != held {
+                    let mut changes = Vec::new();
+                    if let Some(time) = least { changes.push((time, 1)); }
+                    if let Some(time) = held { changes.push((time, -1)); }
+                    write_progress(&mut writer, &changes[..]);
+                    held = least;
+                }
+            },
+            kind => panic!("command: unknown frame kind {}", kind),
+        }
+        writer.flush().unwrap();
+    }
+
+    // stdin has closed; release anything still held.
+    if let Some(time) = held {
+        write_progress(&mut writer, &[(time, -1)]);
+        writer.flush().unwrap();
+    }
+}
+
+fn write_progress<W: Write>(writer: &mut W, changes: &[(u64, i64)]) {
+    let mut body = Vec::new();
+    body.write_u64::<LittleEndian>(changes.len() as u64).unwrap();
+    for &(time, delta) in changes.iter() {
+        body.write_u64::<LittleEndian>(time).unwrap();
+        body.write_i64::<LittleEndian>(delta).unwrap();
+    }
+    write_frame(writer, 1, &body[..]);
+}
+
+fn write_frame<W: Write>(writer: &mut W, kind: u8, body: &[u8]) {
+    writer.write_u64::<LittleEndian>(body.len() as u64 + 1).unwrap();
+    writer.write_u8(kind).unwrap();
+    writer.write_all(body).unwrap();
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> Option<Vec<u8>> {
+    let length = match reader.read_u64::<LittleEndian>() {
+        Ok(length) if length > 0 => length as usize,
+        _ => return None,
+    };
+
+    let mut frame = vec![0u8; length];
+    let mut filled = 0;
+    while filled < length {
+        match reader.read(&mut frame[filled..]) {
+            Ok(0)       => return None,
+            Ok(read)    => filled += read,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => { },
+            Err(_)      => return None,
+        }
+    }
+    Some(frame)
 }
@@ -11,16 +11,29 @@ extern crate docopt;
 use docopt::Docopt;
 
 use std::thread;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::default::Default;
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
 use std::hash::Hash;
+use std::any::Any;
+use std::collections::HashMap;
 use std::fmt::Debug;
 
 use columnar::Columnar;
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
 
-use timely::progress::Scope;
+use timely::progress::{Scope, Subgraph, CountMap, Antichain};
+use timely::progress::nested::Source::GraphInput;
+use timely::progress::nested::Target::GraphOutput;
+use timely::progress::remote::{RemoteScope, serve_scope};
 use timely::progress::nested::Summary::Local;
+use timely::progress::nested::product::Product;
 use timely::progress::timestamp::RootTimestamp;
 use timely::communication::*;
-use timely::communication::pact::Pipeline;
+use timely::communication::channels::ObserverHelper;
+use timely::communication::pact::{Pipeline, Exchange};
 use timely::networking::initialize_networking;
 
 use timely::example_shared::*;
@@ -30,11 +43,14 @@ static USAGE: &'static str = "
 Usage: timely distinct [options] [<arguments>...]
        timely barrier [options] [<arguments>...]
        timely command [options] [<arguments>...]
+       timely remote [options] [<arguments>...]
+       timely progress [options] [<arguments>...]
 
 Options:
     -w <arg>, --workers <arg>    number of workers per process [default: 1]
     -p <arg>, --processid <arg>  identity of this process      [default: 0]
     -n <arg>, --processes <arg>  number of processes involved  [default: 1]
+    -a <arg>, --arity <arg>      arity of the progress tree, or 0 to broadcast progress [default: 0]
 ";
 
 fn main() {
@@ -46,21 +62,31 @@ fn main() {
                           else { panic!("invalid setting for --processid: {}", args.get_str("-p")) };
     let processes: u64 = if let Ok(processes) = args.get_str("-n").parse() { processes }
                          else { panic!("invalid setting for --processes: {}", args.get_str("-n")) };
+    let arity: u64 = if let Ok(arity) = args.get_str("-a").parse() { arity }
+                     else { panic!("invalid setting for --arity: {}", args.get_str("-a")) };
 
     println!("Hello, world!");
     println!("Starting timely with");
     println!("\tworkers:\t{}", workers);
     println!("\tprocesses:\t{}", processes);
     println!("\tprocessid:\t{}", process_id);
+    println!("\tarity:\t\t{}", arity);
+
+    // compares progress aggregated through a tree of leaders with broadcast progress, in one process.
+    if args.get_bool("progress") {
+        _progress_check(workers, if arity > 0 { arity } else { 2 });
+        return;
+    }
 
     // vector holding communicators to use; one per local worker.
     if processes > 1 {
         println!("Initializing BinaryCommunicator");
         let addresses = (0..processes).map(|index| format!("localhost:{}", 2101 + index).to_string()).collect();
-        let communicators = initialize_networking(addresses, process_id, workers).ok().expect("error initializing networking");
+        let communicators = initialize_networking(addresses, process_id, workers, if arity > 0 { Some(arity) } else { None }).ok().expect("error initializing networking");
         if args.get_bool("distinct") { _distinct_multi(communicators); }
         else if args.get_bool("barrier") { _barrier_multi(communicators); }
         else if args.get_bool("command") { _command_multi(communicators); }
+        else if args.get_bool("remote") { _remote_multi(communicators); }
     }
     else if workers > 1 {
         println!("Initializing ProcessCommunicator");
@@ -68,6 +94,7 @@ fn main() {
         if args.get_bool("distinct") { _distinct_multi(communicators); }
         else if args.get_bool("barrier") { _barrier_multi(communicators); }
         else if args.get_bool("command") { _command_multi(communicators); }
+        else if args.get_bool("remote") { _remote_multi(communicators); }
     }
     else {
         println!("Initializing ThreadCommunicator");
@@ -75,6 +102,7 @@ fn main() {
         if args.get_bool("distinct") { _distinct_multi(communicators); }
         else if args.get_bool("barrier") { _barrier_multi(communicators); }
         else if args.get_bool("command") { _command_multi(communicators); }
+        else if args.get_bool("remote") { _remote_multi(communicators); }
     };
 }
 
@@ -93,14 +121,23 @@ fn _distinct_multi<C: Communicator+Send>(communicators: Vec<C>) {
 
 // #[bench]
 // fn command_bench(bencher: &mut Bencher) { _command(ProcessCommunicator::new_vector(1).swap_remove(0).unwrap(), Some(bencher)); }
-fn _command_multi<C: Communicator+Send>(_communicators: Vec<C>) {
-    println!("command currently disabled awaiting io reform");
-    // let mut guards = Vec::new();
-    // for communicator in communicators.into_iter() {
-    //     guards.push(thread::scoped(move || _command(communicator, None)));
-    // }
+fn _command_multi<C: Communicator+Send>(communicators: Vec<C>) {
+    let mut guards = Vec::new();
+    for communicator in communicators.into_iter() {
+        guards.push(thread::spawn(move || _command(communicator)));
+    }
+
+    for guard in guards { guard.join().unwrap(); }
 }
 
+fn _remote_multi<C: Communicator+Send>(communicators: Vec<C>) {
+    let mut guards = Vec::new();
+    for communicator in communicators.into_iter() {
+        guards.push(thread::spawn(move || _remote(communicator)));
+    }
+
+    for guard in guards { guard.join().unwrap(); }
+}
 
 // #[bench]
 // fn barrier_bench(bencher: &mut Bencher) { _barrier(ProcessCommunicator::new_vector(1).swap_remove(0), Some(bencher)); }
@@ -168,31 +205,30 @@ fn _distinct<C: Communicator>(communicator: C) {
     while root.step() { }
 }
 
-// fn _command<C: Communicator>(communicator: C, bencher: Option<&mut Bencher>) {
-//     let communicator = Rc::new(RefCell::new(communicator));
-//
-//     // no "base scopes" yet, so the root pretends to be a subscope of some parent with a () timestamp type.
-//     let mut graph = new_graph(Progcaster::new(&mut (*communicator.borrow_mut())));
-//     let mut input = graph.new_input::<u64>(communicator);
-//     let mut feedback = input.1.feedback(((), 1000), Local(1));
-//     let mut result: Stream<_, u64, _> = input.1.concat(&mut feedback.1)
-//                                                .command("./target/release/command".to_string());
-//
-//     feedback.0.connect_input(&mut result);
-//
-//     // start things up!
-//     graph.borrow_mut().get_internal_summary();
-//     graph.borrow_mut().set_external_summary(Vec::new(), &mut []);
-//     graph.borrow_mut().push_external_progress(&mut []);
-//
-//     input.0.close_at(&((), 0));
-//
-//     // spin
-//     match bencher {
-//         Some(b) => b.iter(|| { graph.borrow_mut().pull_internal_progress(&mut [], &mut [], &mut []); }),
-//         None    => while graph.borrow_mut().pull_internal_progress(&mut [], &mut [], &mut []) { },
-//     }
-// }
+fn _command<C: Communicator>(communicator: C) {
+
+    let mut root = GraphRoot::new(communicator);
+
+    // each worker runs its own copy of the example vertex in src/bin/command.rs.
+    let mut input = root.subcomputation(|graph| {
+        let (input, stream) = graph.new_input::<Vec<u8>>();
+        stream.command(format!("./target/release/command"), vec![])
+              .inspect(|x| println!("command: {}", String::from_utf8_lossy(&x[..])));
+        input
+    });
+
+    root.step();
+
+    for round in 0..10u64 {
+        input.send_at(round, (0..3).map(|index| format!("round {} record {}", round, index).into_bytes()));
+        input.advance_to(round + 1);
+        root.step();
+    }
+
+    input.close();
+
+    while root.step() { }
+}
 
 fn _barrier<C: Communicator>(communicator: C) {
 
@@ -216,3 +252,199 @@ fn _barrier<C: Communicator>(communicator: C) {
     // spin
     while root.step() { }
 }
+
+// hosts a subgraph multiplying its input by ten behind a loopback socket, and drives it through a
+// RemoteScope as a parent scope would. records travel to and from the subgraph on a second socket:
+// they are delivered to its input at RootTimestamp, and its output is written back as it is
+// produced. the subgraph coordinates with those of the other workers through communicator.
+fn _remote<C: Communicator+Send>(communicator: C) {
+
+    let listener = TcpListener::bind("127.0.0.1:0").ok().expect("could not bind");
+    let address = listener.local_addr().ok().expect("could not find address");
+
+    let index = communicator.index();
+
+    let host = thread::spawn(move || {
+        let (mut data, _) = listener.accept().ok().expect("could not accept");
+        let (control, _) = listener.accept().ok().expect("could not accept");
+
+        let mut root = GraphRoot::new(communicator);
+        let builder = SubgraphBuilder {
+            subgraph: Rc::new(RefCell::new(Subgraph::<RootTimestamp, u64>::new_from(&mut root, 0, format!("Hosted")))),
+            parent:   root,
+            members:  None,
+        };
+
+        let counts: Rc<RefCell<CountMap<Product<RootTimestamp, u64>>>> = Default::default();
+        let (targets, registrar) = OutputPort::new();
+        let mut ingress = ObserverHelper::new(targets, counts.clone());
+        let input = builder.subgraph.borrow_mut().new_input(counts);
+        let output = builder.subgraph.borrow_mut().new_output();
+
+        Stream::new(GraphInput(input), registrar, builder.clone())
+            .map(|x: u64| 10 * x)
+            .connect_to(GraphOutput(output), RecordWriter { writer: data.try_clone().unwrap() });
+
+        // the records enter the subgraph as an ingress would deliver them.
+        let count = data.read_u64::<LittleEndian>().ok().expect("read error");
+        ingress.open(&RootTimestamp::new(0));
+        for _ in (0..count) { ingress.give(data.read_u64::<LittleEndian>().ok().expect("read error")); }
+        ingress.shut(&RootTimestamp::new(0));
+
+        let mut subgraph = builder.subgraph.borrow_mut();
+        serve_scope(&mut *subgraph, control.try_clone().unwrap(), control).ok().expect("host error");
+    });
+
+    let records = vec![index, index + 1, index + 2];
+
+    let mut data = TcpStream::connect(address).ok().expect("could not connect");
+    data.write_u64::<LittleEndian>(records.len() as u64).ok().expect("write error");
+    for &record in records.iter() { data.write_u64::<LittleEndian>(record).ok().expect("write error"); }
+
+    let control = TcpStream::connect(address).ok().expect("could not connect");
+    let mut remote = RemoteScope::<RootTimestamp, _, _>::new(control.try_clone().unwrap(), control)
+                                                       .ok().expect("handshake error");
+
+    assert_eq!(remote.name(), "Hosted");
+    assert_eq!(remote.inputs(), 1);
+    assert_eq!(remote.outputs(), 1);
+
+    // the input reaches the output without delay, and the subgraph holds no capabilities.
+    let (summaries, capabilities) = remote.get_internal_summary();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].len(), 1);
+    assert_eq!(summaries[0][0].elements, vec![Default::default()]);
+    assert!(capabilities.len() == 1 && capabilities[0].len() == 0);
+
+    // the input is initially at RootTimestamp, and is then closed.
+    remote.set_external_summary(vec![vec![Antichain::from_elem(Default::default())]],
+                                &mut [CountMap::new_from(&RootTimestamp, 1)]);
+    remote.push_external_progress(&mut [CountMap::new_from(&RootTimestamp, -1)]);
+
+    let mut internal = [CountMap::new()];
+    let mut consumed = [CountMap::new()];
+    let mut produced = [CountMap::new()];
+    while remote.pull_internal_progress(&mut internal, &mut consumed, &mut produced) { }
+
+    assert_eq!(*consumed[0].elements(), vec![(RootTimestamp, records.len() as i64)]);
+    assert_eq!(*produced[0].elements(), vec![(RootTimestamp, records.len() as i64)]);
+
+    // the produced records have been written back by the subgraph's output.
+    let mut results = Vec::new();
+    for _ in (0..records.len()) { results.push(data.read_u64::<LittleEndian>().ok().expect("read error")); }
+    assert_eq!(results, records.iter().map(|x| 10 * x).collect::<Vec<_>>());
+
+    drop(remote);
+    host.join().unwrap();
+    println!("remote: worker {} passed {} records through a hosted subgraph", index, records.len());
+}
+
+// writes the records of the hosted subgraph's output to the client.
+struct RecordWriter {
+    writer: TcpStream,
+}
+
+impl Observer for RecordWriter {
+    type Time = Product<RootTimestamp, u64>;
+    type Data = u64;
+    fn open(&mut self, _time: &Product<RootTimestamp, u64>) { }
+    fn show(&mut self, data: &u64) { self.give(*data); }
+    fn give(&mut self, data: u64) { self.writer.write_u64::<LittleEndian>(data).ok().expect("write error"); }
+    fn shut(&mut self, _time: &Product<RootTimestamp, u64>) { self.writer.flush().ok().expect("flush error"); }
+}
+
+// runs the same computation with progress broadcast between workers, and with the workers grouped
+// two to a simulated process whose leaders aggregate progress in a tree of the given arity, and
+// checks that every worker sees the same notifications with the same records in each case. run as
+// `timely progress -w 8 -a 2`, for four groups in a tree of depth two.
+fn _progress_check(workers: u64, arity: u64) {
+    assert!(workers > 2, "progress: use at least three workers, so that there are several groups");
+
+    let broadcast = _progress_multi(ProcessCommunicator::new_vector(workers));
+    let grouped = ProcessCommunicator::new_vector(workers).into_iter()
+                                                         .map(|communicator| Grouped { inner: communicator, group: 2, arity: arity })
+                                                         .collect();
+    let tree = _progress_multi(grouped);
+
+    let expected: Vec<_> = (0..10).map(|round| (round, 4 * workers)).collect();
+    for (index, (broadcast, tree)) in broadcast.into_iter().zip(tree.into_iter()).enumerate() {
+        assert_eq!(broadcast, expected);
+        assert_eq!(tree, expected);
+        println!("progress: worker {} saw the same {} notifications with broadcast and tree progress", index, tree.len());
+    }
+}
+
+fn _progress_multi<C: Communicator+Send>(communicators: Vec<C>) -> Vec<Vec<(u64, u64)>> {
+    let mut guards = Vec::new();
+    for communicator in communicators.into_iter() {
+        guards.push(thread::spawn(move || _progress(communicator)));
+    }
+
+    guards.into_iter().map(|guard| guard.join().unwrap()).collect()
+}
+
+// each worker sends 4 * peers records in each of ten rounds, exchanged by value, and reports each
+// round's count of records once it is notified that the round is complete.
+fn _progress<C: Communicator>(communicator: C) -> Vec<(u64, u64)> {
+
+    let mut root = GraphRoot::new(communicator);
+    let peers = root.peers();
+    let results = Rc::new(RefCell::new(Vec::new()));
+
+    let mut input = {
+        let results = results.clone();
+        root.subcomputation::<u64, _, _>(move |graph| {
+            let (input, stream) = graph.new_input::<u64>();
+            let mut counts = HashMap::new();
+            stream.unary_notify(Exchange::new(|x: &u64| *x), format!("Count"), vec![], move |input, output, notificator| {
+                      while let Some((time, data)) = input.pull() {
+                          if !counts.contains_key(&time) {
+                              counts.insert(time, 0);
+                              notificator.notify_at(&time);
+                          }
+                          *counts.get_mut(&time).unwrap() += data.len() as u64;
+                      }
+                      while let Some((time, _count)) = notificator.next() {
+                          if let Some(count) = counts.remove(&time) {
+                              output.give_at(&time, Some((time.inner, count)).into_iter());
+                          }
+                      }
+                  })
+                  .inspect(move |&x| results.borrow_mut().push(x));
+            input
+        })
+    };
+
+    root.step();
+
+    for round in (0..10u64) {
+        input.send_at(round, 0..4 * peers);
+        input.advance_to(round + 1);
+        root.step();
+    }
+
+    input.close();
+
+    while root.step() { }
+
+    let result = results.borrow().clone();
+    result
+}
+
+// presents the workers of one process as processes of group workers each, whose leaders aggregate
+// progress in a tree of the given arity, so that Progcaster's relays run without networking.
+struct Grouped<C: Communicator> {
+    inner:  C,
+    group:  u64,
+    arity:  u64,
+}
+
+impl<C: Communicator> Communicator for Grouped<C> {
+    fn index(&self) -> u64 { self.inner.index() }
+    fn peers(&self) -> u64 { self.inner.peers() }
+    fn local_peers(&self) -> u64 { self.group }
+    fn progress_arity(&self) -> Option<u64> { Some(self.arity) }
+    fn new_channel<T:Send+Columnar+Any>(&mut self) -> (Vec<Box<Pushable<T>>>, Box<Pullable<T>>) {
+        self.inner.new_channel()
+    }
+}
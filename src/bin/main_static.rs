@@ -34,6 +34,7 @@ Options:
     -w <arg>, --workers <arg>    number of workers per process [default: 1]
     -p <arg>, --processid <arg>  identity of this process      [default: 0]
     -n <arg>, --processes <arg>  number of processes involved  [default: 1]
+    -a <arg>, --arity <arg>      arity of the progress tree, or 0 to broadcast progress [default: 0]
 ";
 
 fn main() {
@@ -45,18 +46,21 @@ fn main() {
                           else { panic!("invalid setting for --processid: {}", args.get_str("-p")) };
     let processes: u64 = if let Ok(processes) = args.get_str("-n").parse() { processes }
                          else { panic!("invalid setting for --processes: {}", args.get_str("-n")) };
+    let arity: u64 = if let Ok(arity) = args.get_str("-a").parse() { arity }
+                     else { panic!("invalid setting for --arity: {}", args.get_str("-a")) };
 
     println!("Hello, world!");
     println!("Starting timely with");
     println!("\tworkers:\t{}", workers);
     println!("\tprocesses:\t{}", processes);
     println!("\tprocessid:\t{}", process_id);
+    println!("\tarity:\t\t{}", arity);
 
     // vector holding communicators to use; one per local worker.
     if processes > 1 {
         println!("Initializing BinaryCommunicator");
         let addresses = (0..processes).map(|index| format!("localhost:{}", 2101 + index).to_string()).collect();
-        let communicators = initialize_networking(addresses, process_id, workers).ok().expect("error initializing networking");
+        let communicators = initialize_networking(addresses, process_id, workers, if arity > 0 { Some(arity) } else { None }).ok().expect("error initializing networking");
         if args.get_bool("distinct") { _distinct_multi(communicators); }
         else if args.get_bool("barrier") { _barrier_multi(communicators); }
         else if args.get_bool("command") { _command_multi(communicators); }
@@ -20,6 +20,12 @@ pub trait Communicator: 'static {
     fn index(&self) -> u64;     // number out of peers
     fn peers(&self) -> u64;     // number of peers
     fn new_channel<T:Send+Columnar+Any>(&mut self) -> (Vec<Box<Pushable<T>>>, Box<Pullable<T>>);
+
+    // workers are numbered so that those sharing a process are contiguous, local_peers() at a time.
+    fn local_peers(&self) -> u64 { self.peers() }
+
+    // if set, the number of children of each process in a tree used to aggregate progress updates.
+    fn progress_arity(&self) -> Option<u64> { None }
 }
 
 // TODO : Would be nice if Communicator had associated types for its Pushable and Pullable types,
@@ -43,6 +49,36 @@ impl Communicator for ThreadCommunicator {
 }
 
 
+// A view of a Communicator restricted to the workers listed in members, who are indexed by their
+// position in the list. Channels only connect members, but must still be allocated by every worker,
+// as the underlying channels are; a worker that is not a member has index members.len().
+pub struct Subset<C: Communicator> {
+    pub inner:      C,
+    pub members:    Rc<Vec<u64>>,
+}
+
+impl<C: Communicator> Subset<C> {
+    pub fn new(inner: C, members: Rc<Vec<u64>>) -> Subset<C> { Subset { inner: inner, members: members } }
+    pub fn is_member(&self) -> bool { self.members.contains(&self.inner.index()) }
+}
+
+impl<C: Communicator> Communicator for Subset<C> {
+    fn index(&self) -> u64 {
+        let index = self.inner.index();
+        self.members.iter().position(|&x| x == index).unwrap_or(self.members.len()) as u64
+    }
+    fn peers(&self) -> u64 { self.members.len() as u64 }
+    fn local_peers(&self) -> u64 { 1 }    // members need not be grouped by process
+    fn progress_arity(&self) -> Option<u64> { self.inner.progress_arity() }
+    fn new_channel<T:Send+Columnar+Any>(&mut self) -> (Vec<Box<Pushable<T>>>, Box<Pullable<T>>) {
+        let (senders, receiver) = self.inner.new_channel();
+        let mut senders: Vec<Option<Box<Pushable<T>>>> = senders.into_iter().map(Some).collect();
+        let members = self.members.iter().map(|&index| senders[index as usize].take().expect("Subset: repeated or invalid member")).collect();
+        (members, receiver)
+    }
+}
+
+
 // A specific Communicator for inter-thread intra-process communication
 pub struct ProcessCommunicator {
     inner:      ThreadCommunicator,             // inner ThreadCommunicator
@@ -109,6 +145,7 @@ pub struct BinaryCommunicator {
     pub peers:      u64,                    // number of peer workers
     pub graph:      u64,                    // identifier for the current graph
     pub allocated:  u64,                    // indicates how many channels have been allocated (locally).
+    progress_arity: Option<u64>,            // arity of the tree aggregating progress, if any
 
     // for loading up state in the networking threads.
     pub writers:    Vec<Sender<((u64, u64, u64), Sender<Vec<u8>>)>>,                     // (index, back-to-worker)
@@ -117,6 +154,23 @@ pub struct BinaryCommunicator {
 }
 
 impl BinaryCommunicator {
+    pub fn new(inner: ProcessCommunicator, index: u64, peers: u64, progress_arity: Option<u64>,
+               writers: Vec<Sender<((u64, u64, u64), Sender<Vec<u8>>)>>,
+               readers: Vec<Sender<((u64, u64, u64), Sender<Vec<u8>>, Receiver<Vec<u8>>)>>,
+               senders: Vec<Sender<(MessageHeader, Vec<u8>)>>) -> BinaryCommunicator {
+        BinaryCommunicator {
+            inner:          inner,
+            index:          index,
+            peers:          peers,
+            graph:          0,          // TODO : Fix this
+            allocated:      0,
+            progress_arity: progress_arity,
+            writers:        writers,
+            readers:        readers,
+            senders:        senders,
+        }
+    }
+
     pub fn inner<'a>(&'a mut self) -> &'a mut ProcessCommunicator { &mut self.inner }
 }
 
@@ -124,6 +178,8 @@ impl BinaryCommunicator {
 impl Communicator for BinaryCommunicator {
     fn index(&self) -> u64 { self.index }
     fn peers(&self) -> u64 { self.peers }
+    fn local_peers(&self) -> u64 { self.inner.peers() }
+    fn progress_arity(&self) -> Option<u64> { self.progress_arity }
     fn new_channel<T:Send+Columnar+Any>(&mut self) -> (Vec<Box<Pushable<T>>>, Box<Pullable<T>>) {
         let mut pushers: Vec<Box<Pushable<T>>> = Vec::new(); // built-up vector of Box<Pushable<T>> to return
 
@@ -2,6 +2,7 @@ pub use communication::channels::Data;
 pub use communication::allocator::ThreadCommunicator;
 pub use communication::allocator::ProcessCommunicator;
 pub use communication::allocator::BinaryCommunicator;
+pub use communication::allocator::Subset;
 pub use communication::pact::ParallelizationContract;
 pub use communication::observer::{Observer, ObserverSessionExt};
 pub use communication::allocator::{Communicator};
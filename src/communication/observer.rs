@@ -123,6 +123,29 @@ impl<O: Observer, H: Fn(&O::Data) -> u64+'static> Observer for ExchangeObserver<
     #[inline(always)] fn shut(&mut self, time: &O::Time) -> () { for observer in self.observers.iter_mut() { observer.shut(time); } }
 }
 
+// an observer sending each record to the observer at the index it names, without hashing
+pub struct PartitionObserver<O: Observer, H: Fn(&O::Data) -> u64> {
+    pub observers:  Vec<O>,
+    pub index_func: H,
+}
+
+impl<O: Observer, H: Fn(&O::Data) -> u64+'static> Observer for PartitionObserver<O, H> where O::Data : Clone {
+    type Time = O::Time;
+    type Data = O::Data;
+    #[inline(always)] fn open(&mut self, time: &O::Time) -> () { for observer in self.observers.iter_mut() { observer.open(time); } }
+    #[inline(always)] fn show(&mut self, data: &O::Data) -> () {
+        let dst = (self.index_func)(data);
+        assert!(dst < self.observers.len() as u64, "PartitionObserver: index {} out of range", dst);
+        self.observers[dst as usize].show(data);
+    }
+    #[inline(always)] fn give(&mut self, data:  O::Data) -> () {
+        let dst = (self.index_func)(&data);
+        assert!(dst < self.observers.len() as u64, "PartitionObserver: index {} out of range", dst);
+        self.observers[dst as usize].give(data);
+    }
+    #[inline(always)] fn shut(&mut self, time: &O::Time) -> () { for observer in self.observers.iter_mut() { observer.shut(time); } }
+}
+
 // // an observer buffering records before sending
 // pub struct BufferedObserver<D, O: Observer> {
 //     limit:      usize,
@@ -7,7 +7,7 @@ use std::collections::VecDeque;
 use progress::Timestamp;
 use communication::Data;
 use communication::{Communicator, Pullable, Pushable, Observer};
-use communication::observer::ExchangeObserver;
+use communication::observer::{ExchangeObserver, PartitionObserver};
 
 use columnar::Columnar;
 
@@ -65,6 +65,35 @@ impl<T: Timestamp, D: Data+Columnar, F: Fn(&D)->u64+'static> ParallelizationCont
     }
 }
 
+// sends each record to the worker whose index func returns, which must be less than the number of
+// workers. unlike Exchange, func names the worker itself rather than a hash of the record.
+pub struct Partition<D, F: Fn(&D)->u64> { index_func: F, phantom: PhantomData<D>, }
+impl<D, F: Fn(&D)->u64> Partition<D, F> {
+    pub fn new(func: F) -> Partition<D, F> {
+        Partition {
+            index_func: func,
+            phantom:    PhantomData,
+        }
+    }
+}
+
+impl<T: Timestamp, D: Data+Columnar, F: Fn(&D)->u64+'static> ParallelizationContract<T, D> for Partition<D, F> {
+    type Observer = PartitionObserver<PactObserver<T, D, Box<Pushable<(T, Vec<D>)>>>, F>;
+    type Pullable = Box<Pullable<(T, Vec<D>)>>;
+    fn connect<C: Communicator>(self, communicator: &mut C) -> (Self::Observer, PactPullable<T, D, Self::Pullable>) {
+        let (senders, receiver) = communicator.new_channel();
+
+        let shared = Rc::new(RefCell::new(Vec::new()));
+
+        let partition_sender = PartitionObserver {
+            observers:  senders.into_iter().map(|x| PactObserver::new(x, shared.clone())).collect(),
+            index_func: self.index_func,
+        };
+
+        return (partition_sender, PactPullable::new(receiver, shared));
+    }
+}
+
 pub struct PactPullable<T:Send, D:Send+Clone, P: Pullable<(T, Vec<D>)>> {
     pullable: P,
     buffer:   Vec<D>,
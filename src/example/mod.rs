@@ -19,7 +19,6 @@ pub mod queue;
 pub mod stream;
 pub mod barrier;
 pub mod graph_builder;
-// pub mod command;     // awaiting old_io -> io completion.
 pub mod map;
 pub mod flat_map;
 pub mod filter;
@@ -2,11 +2,12 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::any::Any;
 
-use progress::{Timestamp, Scope, Subgraph};
+use progress::{Timestamp, Scope, Subgraph, CountMap, Antichain};
 use progress::nested::{Source, Target};
 use progress::nested::product::Product;
 use progress::nested::scope_wrapper::ScopeWrapper;
-use communication::{Pushable, Pullable, Communicator};
+use progress::nested::region::RegionWrapper;
+use communication::{Pushable, Pullable, Communicator, Subset};
 use progress::timestamp::RootTimestamp;
 
 use columnar::Columnar;
@@ -28,7 +29,14 @@ pub trait GraphBuilder : Communicator+Clone {
 
     fn add_scope<SC: Scope<Self::Timestamp>+'static>(&self, scope: SC) -> u64;  // returns name
 
-    fn new_subscope<T: Timestamp>(&mut self) -> Subgraph<Self::Timestamp, T>;
+    // a subgraph named by the index the next call to add_scope will return, exchanging progress
+    // through communicator.
+    fn new_subscope_from<T: Timestamp, C: Communicator>(&mut self, communicator: &mut C) -> Subgraph<Self::Timestamp, T>;
+
+    fn new_subscope<T: Timestamp>(&mut self) -> Subgraph<Self::Timestamp, T> {
+        let mut communicator = self.clone();
+        self.new_subscope_from(&mut communicator)
+    }
 
     // TODO : Learn about the scoped() pattern that prevents the subgraph builder from escaping
     fn subcomputation<T: Timestamp, R, F:FnOnce(&SubgraphBuilder<Self, T>)->R>(&mut self, func: F) -> R {
@@ -36,6 +44,7 @@ pub trait GraphBuilder : Communicator+Clone {
         let builder = SubgraphBuilder {
             subgraph: subscope,
             parent: self.clone(),
+            members: None,
         };
 
         let result = func(&builder);
@@ -53,6 +62,43 @@ pub trait GraphBuilder : Communicator+Clone {
         //     panic!("subcomputation failed to get unique handle to subgraph builder");
         // }
     }
+
+    // as subcomputation, but coordinated only by the workers whose indices are in members, to which
+    // the subgraph appears as the whole computation. the subgraph is built by every worker, so that
+    // channels line up, but is only run by members; records entered into it on other workers panic.
+    fn subcomputation_among<T: Timestamp, R, F:FnOnce(&SubgraphBuilder<Self, T>)->R>(&mut self, members: Vec<u64>, func: F) -> R {
+        let members = Rc::new(members);
+        let mut view = Subset::new(self.clone(), members.clone());
+        let builder = SubgraphBuilder {
+            subgraph: Rc::new(RefCell::new(self.new_subscope_from(&mut view))),
+            parent: self.clone(),
+            members: Some(members),
+        };
+
+        let result = func(&builder);
+
+        if view.is_member() { self.add_scope(builder.subgraph); }
+        else                { self.add_scope(InertScope { scope: builder.subgraph }); }
+
+        result
+    }
+
+    // groups the operators built by func in a named child scope with the same timestamp type.
+    // streams move in and out of the region with enter_region and leave_region.
+    fn region<R, F:FnOnce(&RegionBuilder<Self>)->R>(&mut self, name: &str, func: F) -> R {
+        let mut subgraph = self.new_subscope::<RootTimestamp>();
+        subgraph.name = format!("{}::{}[{}]", self.name(), name, subgraph.index);
+        let builder = RegionBuilder {
+            subgraph: Rc::new(RefCell::new(subgraph)),
+            parent: self.clone(),
+        };
+
+        let result = func(&builder);
+
+        self.add_scope(builder.subgraph);
+
+        result
+    }
 }
 
 // impl<G: GraphBuilder> GraphBuilder for Rc<RefCell<G>> {
@@ -103,15 +149,17 @@ impl<C: Communicator> GraphBuilder for GraphRoot<C> {
         else { panic!("GraphRoot::add_scope(): added second scope to root") }
     }
 
-    fn new_subscope<T: Timestamp>(&mut self) -> Subgraph<RootTimestamp, T>  {
+    fn new_subscope_from<T: Timestamp, C2: Communicator>(&mut self, communicator: &mut C2) -> Subgraph<RootTimestamp, T>  {
         let name = format!("{}::Subgraph[0]", self.name());
-        Subgraph::new_from(&mut (*self.communicator.borrow_mut()), 0, name)
+        Subgraph::new_from(communicator, 0, name)
     }
 }
 
 impl<C: Communicator> Communicator for GraphRoot<C> {
     fn index(&self) -> u64 { self.communicator.borrow().index() }
     fn peers(&self) -> u64 { self.communicator.borrow().peers() }
+    fn local_peers(&self) -> u64 { self.communicator.borrow().local_peers() }
+    fn progress_arity(&self) -> Option<u64> { self.communicator.borrow().progress_arity() }
     fn new_channel<T:Send+Columnar+Any>(&mut self) -> (Vec<Box<Pushable<T>>>, Box<Pullable<T>>) {
         self.communicator.borrow_mut().new_channel()
     }
@@ -125,6 +173,17 @@ impl<C: Communicator> Clone for GraphRoot<C> {
 pub struct SubgraphBuilder<G: GraphBuilder, T: Timestamp> {
     pub subgraph: Rc<RefCell<Subgraph<G::Timestamp, T>>>,
     pub parent:   G,
+    pub members:  Option<Rc<Vec<u64>>>,  // workers coordinating the subgraph, if not all of them
+}
+
+impl<G: GraphBuilder, T: Timestamp> SubgraphBuilder<G, T> {
+    // true unless the subgraph is coordinated by a subset of workers excluding this one.
+    pub fn is_member(&self) -> bool {
+        match self.members {
+            Some(ref members) => members.contains(&self.parent.index()),
+            None              => true,
+        }
+    }
 }
 
 // impl<G: GraphBuilder, T: Timestamp> Drop for SubgraphBuilder<G, T> {
@@ -150,21 +209,113 @@ impl<G: GraphBuilder, T: Timestamp> GraphBuilder for SubgraphBuilder<G, T> {
         index
     }
 
-    fn new_subscope<T2: Timestamp>(&mut self) -> Subgraph<Product<G::Timestamp, T>, T2> {
+    fn new_subscope_from<T2: Timestamp, C: Communicator>(&mut self, communicator: &mut C) -> Subgraph<Product<G::Timestamp, T>, T2> {
         let index = self.subgraph.borrow().children() as u64;
         let name = format!("{}::Subgraph[{}]", self.name(), index);
-        Subgraph::new_from(self, index, name)
+        Subgraph::new_from(communicator, index, name)
     }
 }
 
 impl<G: GraphBuilder, T: Timestamp> Communicator for SubgraphBuilder<G, T> {
+    fn index(&self) -> u64 {
+        match self.members {
+            Some(ref members) => Subset::new(self.parent.clone(), members.clone()).index(),
+            None              => self.parent.index(),
+        }
+    }
+    fn peers(&self) -> u64 {
+        match self.members {
+            Some(ref members) => members.len() as u64,
+            None              => self.parent.peers(),
+        }
+    }
+    fn local_peers(&self) -> u64 {
+        match self.members {
+            Some(ref members) => Subset::new(self.parent.clone(), members.clone()).local_peers(),
+            None              => self.parent.local_peers(),
+        }
+    }
+    fn progress_arity(&self) -> Option<u64> { self.parent.progress_arity() }
+    fn new_channel<D:Send+Columnar+Any>(&mut self) -> (Vec<Box<Pushable<D>>>, Box<Pullable<D>>) {
+        match self.members {
+            Some(ref members) => Subset::new(self.parent.clone(), members.clone()).new_channel(),
+            None              => self.parent.new_channel(),
+        }
+    }
+}
+
+impl<G: GraphBuilder, T: Timestamp> Clone for SubgraphBuilder<G, T> {
+    fn clone(&self) -> Self {
+        SubgraphBuilder { subgraph: self.subgraph.clone(), parent: self.parent.clone(), members: self.members.clone() }
+    }
+}
+
+// stands in for a subgraph on a worker that does not coordinate it. it describes the subgraph as
+// its members do, so that all workers agree on the initial state of the parent, but it is never run
+// and reports no progress of its own; the members report the subgraph's progress for it.
+struct InertScope<S> {
+    scope: S,
+}
+
+impl<T: Timestamp, S: Scope<T>> Scope<T> for InertScope<S> {
+    fn name(&self) -> String { format!("{}(inert)", self.scope.name()) }
+    fn inputs(&self) -> u64 { self.scope.inputs() }
+    fn outputs(&self) -> u64 { self.scope.outputs() }
+
+    fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<T::Summary>>>, Vec<CountMap<T>>) {
+        self.scope.get_internal_summary()
+    }
+
+    fn set_external_summary(&mut self, _summaries: Vec<Vec<Antichain<T::Summary>>>, frontier: &mut [CountMap<T>]) {
+        for changes in frontier.iter_mut() { changes.clear(); }
+    }
+
+    fn pull_internal_progress(&mut self, _internal: &mut [CountMap<T>],
+                                         _consumed: &mut [CountMap<T>],
+                                         _produced: &mut [CountMap<T>]) -> bool { false }
+
+    fn notify_me(&self) -> bool { false }
+}
+
+
+pub struct RegionBuilder<G: GraphBuilder> {
+    pub subgraph: Rc<RefCell<Subgraph<G::Timestamp, RootTimestamp>>>,
+    pub parent:   G,
+}
+
+impl<G: GraphBuilder> GraphBuilder for RegionBuilder<G> {
+    type Timestamp = G::Timestamp;
+
+    fn name(&self) -> String { self.subgraph.borrow().name() }
+    fn add_edge(&self, source: Source, target: Target) {
+        self.subgraph.borrow_mut().connect(source, target);
+    }
+
+    fn add_scope<SC: Scope<Self::Timestamp>+'static>(&self, scope: SC) -> u64 {
+        let index = self.subgraph.borrow().children.len() as u64;
+        let name = format!("{}", self.name());
+        let scope = RegionWrapper::new(scope);
+        self.subgraph.borrow_mut().children.push(ScopeWrapper::new(Box::new(scope), index, name));
+        index
+    }
+
+    fn new_subscope_from<T: Timestamp, C: Communicator>(&mut self, communicator: &mut C) -> Subgraph<G::Timestamp, T> {
+        let index = self.subgraph.borrow().children() as u64;
+        let name = format!("{}::Subgraph[{}]", self.name(), index);
+        Subgraph::new_from(communicator, index, name)
+    }
+}
+
+impl<G: GraphBuilder> Communicator for RegionBuilder<G> {
     fn index(&self) -> u64 { self.parent.index() }
     fn peers(&self) -> u64 { self.parent.peers() }
+    fn local_peers(&self) -> u64 { self.parent.local_peers() }
+    fn progress_arity(&self) -> Option<u64> { self.parent.progress_arity() }
     fn new_channel<D:Send+Columnar+Any>(&mut self) -> (Vec<Box<Pushable<D>>>, Box<Pullable<D>>) {
         self.parent.new_channel()
     }
 }
 
-impl<G: GraphBuilder, T: Timestamp> Clone for SubgraphBuilder<G, T> {
-    fn clone(&self) -> Self { SubgraphBuilder { subgraph: self.subgraph.clone(), parent: self.parent.clone() }}
+impl<G: GraphBuilder> Clone for RegionBuilder<G> {
+    fn clone(&self) -> Self { RegionBuilder { subgraph: self.subgraph.clone(), parent: self.parent.clone() }}
 }
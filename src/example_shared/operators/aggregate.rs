@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::mem;
+use std::hash::Hash;
+use std::ops::Add;
+
+use communication::*;
+use communication::pact::{Pipeline, Exchange};
+
+use example_shared::*;
+use example_shared::operators::unary::{UnaryStreamExt, UnaryNotifyExt};
+use example_shared::operators::map::MapExt;
+use example_shared::operators::hashing::hash_code;
+
+use columnar::Columnar;
+
+use drain::DrainExt;
+
+// Keyed aggregations route records by key, accumulate them per time, and produce one record per
+// key and time once the time is complete. Where many records share a key, pre_reduce_by_key may
+// be applied first to combine records within each batch before they are exchanged, for example
+// `stream.pre_reduce_by_key(|x,y| x + y).reduce_by_key(|x,y| x + y)`.
+pub trait AggregateExt<G: GraphBuilder, K: Data+Hash+Eq+Columnar, V: Data+Columnar> {
+    fn group_by_key(&self) -> Stream<G, (K, Vec<V>)>;
+    fn reduce_by_key<F: Fn(V, V)->V+'static>(&self, func: F) -> Stream<G, (K, V)>;
+    fn pre_reduce_by_key<F: Fn(V, V)->V+'static>(&self, func: F) -> Stream<G, (K, V)>;
+    fn count_by_key(&self) -> Stream<G, (K, u64)>;
+    fn sum_by_key(&self) -> Stream<G, (K, V)> where V: Add<Output=V>;
+}
+
+impl<G: GraphBuilder, K: Data+Hash+Eq+Columnar, V: Data+Columnar> AggregateExt<G, K, V> for Stream<G, (K, V)>
+where G::Timestamp: Hash {
+
+    fn group_by_key(&self) -> Stream<G, (K, Vec<V>)> {
+        let mut groups: HashMap<G::Timestamp, HashMap<K, Vec<V>>> = HashMap::new();
+        let exch = Exchange::new(|x: &(K, V)| hash_code(&x.0));
+        self.unary_notify(exch, format!("GroupByKey"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                if !groups.contains_key(&time) {
+                    groups.insert(time, HashMap::new());
+                    notificator.notify_at(&time);
+                }
+                let groups = groups.get_mut(&time).unwrap();
+                for (key, val) in data.drain_temp() {
+                    groups.entry(key).or_insert(Vec::new()).push(val);
+                }
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some(groups) = groups.remove(&time) {
+                    output.give_at(&time, groups.into_iter());
+                }
+            }
+        })
+    }
+
+    fn reduce_by_key<F: Fn(V, V)->V+'static>(&self, func: F) -> Stream<G, (K, V)> {
+        let mut aggs: HashMap<G::Timestamp, HashMap<K, V>> = HashMap::new();
+        let exch = Exchange::new(|x: &(K, V)| hash_code(&x.0));
+        self.unary_notify(exch, format!("ReduceByKey"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                if !aggs.contains_key(&time) {
+                    aggs.insert(time, HashMap::new());
+                    notificator.notify_at(&time);
+                }
+                let aggs = aggs.get_mut(&time).unwrap();
+                for (key, val) in data.drain_temp() {
+                    let val = match aggs.remove(&key) {
+                        Some(prev) => func(prev, val),
+                        None       => val,
+                    };
+                    aggs.insert(key, val);
+                }
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some(aggs) = aggs.remove(&time) {
+                    output.give_at(&time, aggs.into_iter());
+                }
+            }
+        })
+    }
+
+    // combines records with the same key within each batch, without exchanging or waiting for
+    // notification. the output may contain several records for each key and time.
+    fn pre_reduce_by_key<F: Fn(V, V)->V+'static>(&self, func: F) -> Stream<G, (K, V)> {
+        let mut aggs: HashMap<K, V> = HashMap::new();
+        self.unary_stream(Pipeline, format!("PreReduceByKey"), move |input, output| {
+            while let Some((time, data)) = input.pull() {
+                for (key, val) in data.drain_temp() {
+                    let val = match aggs.remove(&key) {
+                        Some(prev) => func(prev, val),
+                        None       => val,
+                    };
+                    aggs.insert(key, val);
+                }
+                output.give_at(&time, mem::replace(&mut aggs, HashMap::new()).into_iter());
+            }
+        })
+    }
+
+    fn count_by_key(&self) -> Stream<G, (K, u64)> {
+        self.map(|(key, _)| (key, 1u64))
+            .pre_reduce_by_key(|x, y| x + y)
+            .reduce_by_key(|x, y| x + y)
+    }
+
+    fn sum_by_key(&self) -> Stream<G, (K, V)> where V: Add<Output=V> {
+        self.reduce_by_key(|x, y| x + y)
+    }
+}
@@ -55,6 +55,50 @@ impl<G: GraphBuilder, D1: Data> BinaryStreamExt<G, D1> for Stream<G, D1> {
     }
 }
 
+// like binary_stream, but the logic may also inspect the frontiers of its two inputs. the operator
+// receives progress updates, but holds no capabilities and cannot request notifications.
+pub trait BinaryFrontierExt<G: GraphBuilder, D1: Data> {
+    fn binary_frontier<D2: Data,
+              D3: Data,
+              L: FnMut(&mut PullableHelper<G::Timestamp, D1, P1::Pullable>,
+                       &mut PullableHelper<G::Timestamp, D2, P2::Pullable>,
+                       &mut ObserverHelper<OutputPort<G::Timestamp, D3>>,
+                       &[G::Timestamp],
+                       &[G::Timestamp])+'static,
+              P1: ParallelizationContract<G::Timestamp, D1>,
+              P2: ParallelizationContract<G::Timestamp, D2>>
+            (&self, &Stream<G, D2>, pact1: P1, pact2: P2, name: String, logic: L) -> Stream<G, D3>;
+}
+
+impl<G: GraphBuilder, D1: Data> BinaryFrontierExt<G, D1> for Stream<G, D1> {
+    fn binary_frontier<
+             D2: Data,
+             D3: Data,
+             L: FnMut(&mut PullableHelper<G::Timestamp, D1, P1::Pullable>,
+                      &mut PullableHelper<G::Timestamp, D2, P2::Pullable>,
+                      &mut ObserverHelper<OutputPort<G::Timestamp, D3>>,
+                      &[G::Timestamp],
+                      &[G::Timestamp])+'static,
+             P1: ParallelizationContract<G::Timestamp, D1>,
+             P2: ParallelizationContract<G::Timestamp, D2>>
+             (&self, other: &Stream<G, D2>, pact1: P1, pact2: P2, name: String, mut logic: L) -> Stream<G, D3> {
+
+        let mut builder = self.builder();
+
+        let (sender1, receiver1) = pact1.connect(&mut builder);
+        let (sender2, receiver2) = pact2.connect(&mut builder);
+        let (targets, registrar) = OutputPort::<G::Timestamp,D3>::new();
+        let notify = Some((vec![], builder.peers()));   // interested in progress, but no capabilities
+        let scope = BinaryScope::new(receiver1, receiver2, targets, name, notify,
+                                     move |i1, i2, o, n| logic(i1, i2, o, n.frontier(0), n.frontier(1)));
+        let index = builder.add_scope(scope);
+        self.connect_to(ScopeInput(index, 0), sender1);
+        other.connect_to(ScopeInput(index, 1), sender2);
+
+        Stream::new(ScopeOutput(index, 0), registrar, builder)
+    }
+}
+
 pub trait BinaryNotifyExt<G: GraphBuilder, D1: Data> {
     fn binary_notify<D2: Data,
               D3: Data,
@@ -0,0 +1,38 @@
+use communication::*;
+use communication::pact::Pipeline;
+
+use example_shared::*;
+use example_shared::operators::generic::OperatorBuilder;
+
+use drain::DrainExt;
+
+pub trait BranchExt<G: GraphBuilder, D: Data> {
+    // routes each record to the first stream if func returns Ok, and to the second if it returns Err.
+    fn branch<D1: Data, D2: Data, F: Fn(D)->Result<D1, D2>+'static>(&self, func: F) -> (Stream<G, D1>, Stream<G, D2>);
+}
+
+impl<G: GraphBuilder, D: Data> BranchExt<G, D> for Stream<G, D> {
+    fn branch<D1: Data, D2: Data, F: Fn(D)->Result<D1, D2>+'static>(&self, func: F) -> (Stream<G, D1>, Stream<G, D2>) {
+
+        let mut builder = OperatorBuilder::new(format!("Branch"), self.builder());
+
+        let mut input = builder.new_input(self, Pipeline);
+        let (mut output1, stream1) = builder.new_output();
+        let (mut output2, stream2) = builder.new_output();
+
+        builder.build(move || {
+            while let Some((time, data)) = input.pull() {
+                let mut session1 = output1.session(&time);
+                let mut session2 = output2.session(&time);
+                for datum in data.drain_temp() {
+                    match func(datum) {
+                        Ok(datum)  => session1.give(datum),
+                        Err(datum) => session2.give(datum),
+                    }
+                }
+            }
+        });
+
+        (stream1.stream(), stream2.stream())
+    }
+}
@@ -0,0 +1,112 @@
+use std::default::Default;
+
+use progress::{Timestamp, Scope, Antichain};
+use progress::frontier::MutableAntichain;
+use progress::nested::subgraph::Target::ScopeInput;
+use progress::count_map::CountMap;
+
+use communication::*;
+use communication::pact::{Pipeline, PactPullable};
+
+use example_shared::*;
+use example_shared::operators::unary::PullableHelper;
+
+use drain::DrainExt;
+
+// The events describing a captured stream. Progress reports changes to the frontier of the stream,
+// which is initially the single element Default::default(); Messages are records at a time that
+// the frontier has not yet passed. Once the frontier is empty, the stream is complete.
+#[derive(Clone, Debug)]
+pub enum Event<T, D> {
+    Progress(Vec<(T, i64)>),
+    Messages(T, Vec<D>),
+}
+
+pub trait CaptureExt<G: GraphBuilder, D: Data> {
+    // sends the records of the stream, and changes to its frontier, to pushable. each worker
+    // captures the records it receives, and the frontier of the whole stream.
+    fn capture_into<P: Pushable<Event<G::Timestamp, D>>+'static>(&self, pushable: P);
+}
+
+impl<G: GraphBuilder, D: Data> CaptureExt<G, D> for Stream<G, D> {
+    fn capture_into<P: Pushable<Event<G::Timestamp, D>>+'static>(&self, pushable: P) {
+
+        let mut builder = self.builder();
+
+        let (sender, receiver) = Pipeline.connect(&mut builder);
+        let index = builder.add_scope(CaptureScope::new(receiver, pushable));
+        self.connect_to(ScopeInput(index, 0), sender);
+    }
+}
+
+pub struct CaptureScope<T: Timestamp, D: Data, P: Pullable<(T, Vec<D>)>, E: Pushable<Event<T, D>>> {
+    input:      PullableHelper<T, D, P>,
+    events:     E,
+    frontier:   MutableAntichain<T>,
+    changes:    CountMap<T>,
+}
+
+impl<T: Timestamp, D: Data, P: Pullable<(T, Vec<D>)>, E: Pushable<Event<T, D>>> CaptureScope<T, D, P, E> {
+    pub fn new(input: PactPullable<T, D, P>, events: E) -> CaptureScope<T, D, P, E> {
+        CaptureScope {
+            input:      PullableHelper::new(input),
+            events:     events,
+            frontier:   MutableAntichain::new_bottom(Default::default()),
+            changes:    CountMap::new(),
+        }
+    }
+
+    // applies frontier updates, accumulating changes in the frontier's elements.
+    fn update_frontier(&mut self, updates: &mut CountMap<T>) {
+        while let Some((time, delta)) = updates.pop() {
+            self.frontier.update_weight(&time, delta, &mut self.changes);
+        }
+    }
+
+    // reports accumulated changes in the frontier, if any.
+    fn flush_frontier(&mut self) {
+        if self.changes.len() > 0 {
+            let changes = self.changes.elements().clone();
+            self.changes.clear();
+            self.events.push(Event::Progress(changes));
+        }
+    }
+}
+
+impl<T: Timestamp, D: Data, P: Pullable<(T, Vec<D>)>, E: Pushable<Event<T, D>>> Scope<T> for CaptureScope<T, D, P, E> {
+    fn name(&self) -> String { format!("Capture") }
+    fn inputs(&self) -> u64 { 1 }
+    fn outputs(&self) -> u64 { 0 }
+
+    fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<T::Summary>>>, Vec<CountMap<T>>) {
+        (vec![vec![]], vec![])
+    }
+
+    fn set_external_summary(&mut self, _summaries: Vec<Vec<Antichain<T::Summary>>>,
+                                       frontier: &mut [CountMap<T>]) -> () {
+        // the initial frontier replaces the Default::default() the event stream starts from.
+        self.update_frontier(&mut frontier[0]);
+        self.update_frontier(&mut CountMap::new_from(&Default::default(), -1));
+        self.flush_frontier();
+    }
+
+    fn push_external_progress(&mut self, external: &mut [CountMap<T>]) -> () {
+        self.update_frontier(&mut external[0]);
+        self.flush_frontier();
+    }
+
+    fn pull_internal_progress(&mut self, _internal: &mut [CountMap<T>],
+                                          consumed: &mut [CountMap<T>],
+                                         _produced: &mut [CountMap<T>]) -> bool
+    {
+        while let Some((time, data)) = self.input.pull() {
+            if data.len() > 0 {
+                self.events.push(Event::Messages(time, data.drain_temp().collect()));
+            }
+        }
+
+        self.input.pull_progress(&mut consumed[0]);
+
+        return false;   // no unannounced internal work
+    }
+}
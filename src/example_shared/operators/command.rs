@@ -0,0 +1,277 @@
+use std::io::{Read, Write, BufWriter, ErrorKind};
+use std::process::{Command, Child, ChildStdin, Stdio};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::thread;
+use std::default::Default;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+
+use progress::{Timestamp, Scope, Antichain};
+use progress::nested::product::Product;
+use progress::nested::subgraph::Source::ScopeOutput;
+use progress::nested::subgraph::Target::ScopeInput;
+use progress::timestamp::RootTimestamp;
+use progress::count_map::CountMap;
+
+use communication::*;
+use communication::channels::ObserverHelper;
+use communication::pact::Pipeline;
+
+use example_shared::*;
+use example_shared::operators::unary::PullableHelper;
+
+use drain::DrainExt;
+
+// The command operator runs an external program as a dataflow vertex, with one input and one
+// output of byte strings, at timestamps Product<RootTimestamp, u64>. It exchanges frames with the
+// program over its stdin and stdout, where each frame is a little-endian u64 length, then that many
+// bytes: a kind byte and a body. Times are written as their u64 inner coordinate.
+//
+// The program is sent, on its stdin:
+//   0 (data):      u64 time, u64 count, then count records, each a u64 length and its bytes.
+//   1 (frontier):  u64 count, then count (u64 time, i64 delta) changes to the input frontier.
+//
+// The program sends, on its stdout:
+//   0 (data):      as above; records to produce at time.
+//   1 (progress):  as frontier above; changes to the capabilities the program holds.
+//
+// The first frame the program sends must be progress describing its initial capabilities, which
+// should be the same for the program of every worker. It is awaited without a timeout while the
+// dataflow is built, so a program that never sends it blocks construction of the graph. The program may only produce data at times
+// for which it holds a capability, and should retain a capability for a time until the input
+// frontier has passed it and it has produced all records for it. Data sent to the program always
+// precedes the frontier changes that account for it. Once the input frontier is empty the program's
+// stdin is closed, and it should release its capabilities and exit. A program that exits while
+// still holding capabilities would stall the dataflow, and instead causes a panic.
+
+const DATA: u8 = 0;
+const FRONTIER: u8 = 1;
+const PROGRESS: u8 = 1;
+
+pub trait CommandExt<G: GraphBuilder<Timestamp=Product<RootTimestamp, u64>>> {
+    fn command(&self, program: String, arguments: Vec<String>) -> Stream<G, Vec<u8>>;
+}
+
+impl<G: GraphBuilder<Timestamp=Product<RootTimestamp, u64>>> CommandExt<G> for Stream<G, Vec<u8>> {
+    fn command(&self, program: String, arguments: Vec<String>) -> Stream<G, Vec<u8>> {
+
+        let mut builder = self.builder();
+
+        let mut process = match Command::new(&program).args(&arguments[..])
+                                                      .stdin(Stdio::piped())
+                                                      .stdout(Stdio::piped())
+                                                      .spawn() {
+            Ok(process) => process,
+            Err(e)      => panic!("Process creation error: {}; program: {}", e, program),
+        };
+
+        let stdin = process.stdin.take().unwrap();
+        let mut stdout = process.stdout.take().unwrap();
+
+        // frames are read on their own thread, so that reading never blocks the worker.
+        let (sender, frames) = channel();
+        thread::spawn(move || {
+            while let Some(frame) = read_frame(&mut stdout) {
+                if sender.send(frame).is_err() { break; }
+            }
+        });
+
+        let (targets, registrar) = OutputPort::new();
+        let produced = Default::default();
+
+        let (sender, receiver) = Pipeline.connect(&mut builder);
+        let index = builder.add_scope(CommandScope {
+            process:    process,
+            stdin:      Some(BufWriter::new(stdin)),
+            frames:     frames,
+            input:      PullableHelper::new(receiver),
+            output:     ObserverHelper::new(targets, produced),
+            frontier:   CountMap::new(),
+            held:       CountMap::new(),
+            copies:     builder.peers(),
+        });
+
+        self.connect_to(ScopeInput(index, 0), sender);
+        Stream::new(ScopeOutput(index, 0), registrar, builder)
+    }
+}
+
+pub struct CommandScope<P: Pullable<(Product<RootTimestamp, u64>, Vec<Vec<u8>>)>> {
+    process:    Child,
+    stdin:      Option<BufWriter<ChildStdin>>,
+    frames:     Receiver<Vec<u8>>,
+    input:      PullableHelper<Product<RootTimestamp, u64>, Vec<u8>, P>,
+    output:     ObserverHelper<OutputPort<Product<RootTimestamp, u64>, Vec<u8>>>,
+    frontier:   CountMap<Product<RootTimestamp, u64>>,
+    held:       CountMap<u64>,  // the capabilities held by this worker's program
+    copies:     u64,
+}
+
+impl<P: Pullable<(Product<RootTimestamp, u64>, Vec<Vec<u8>>)>> CommandScope<P> {
+    // sends changes to the input frontier, closing stdin once the frontier is empty.
+    fn send_frontier(&mut self, changes: &mut CountMap<Product<RootTimestamp, u64>>) {
+        if changes.len() > 0 {
+            let mut body = Vec::new();
+            body.write_u64::<LittleEndian>(changes.len() as u64).unwrap();
+            while let Some((time, delta)) = changes.pop() {
+                self.frontier.update(&time, delta);
+                body.write_u64::<LittleEndian>(time.inner).unwrap();
+                body.write_i64::<LittleEndian>(delta).unwrap();
+            }
+
+            if let Some(ref mut stdin) = self.stdin {
+                write_frame(stdin, FRONTIER, &body[..]).ok().expect("Command: write error");
+                stdin.flush().ok().expect("Command: flush error");
+            }
+            if self.frontier.len() == 0 { self.stdin = None; }
+        }
+    }
+}
+
+impl<P: Pullable<(Product<RootTimestamp, u64>, Vec<Vec<u8>>)>> Scope<Product<RootTimestamp, u64>> for CommandScope<P> {
+    fn name(&self) -> String { format!("Command") }
+    fn inputs(&self) -> u64 { 1 }
+    fn outputs(&self) -> u64 { 1 }
+
+    fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<<Product<RootTimestamp, u64> as Timestamp>::Summary>>>,
+                                           Vec<CountMap<Product<RootTimestamp, u64>>>) {
+        // blocks until the program reports, as the capabilities are needed to build the graph.
+        let frame = self.frames.recv().ok().expect("Command: program exited before reporting capabilities");
+        let mut slice = &frame[..];
+        if slice.read_u8().ok() != Some(PROGRESS) {
+            panic!("Command: program must first report its initial capabilities");
+        }
+
+        // each worker's program reports its own capabilities, and the initial capabilities of an
+        // operator are those of all workers.
+        let mut internal = CountMap::new();
+        for (time, delta) in read_changes(&mut slice).into_iter() {
+            self.held.update(&time, delta);
+            internal.update(&RootTimestamp::new(time), delta * self.copies as i64);
+        }
+
+        (vec![vec![Antichain::from_elem(Default::default())]], vec![internal])
+    }
+
+    fn set_external_summary(&mut self, _summaries: Vec<Vec<Antichain<<Product<RootTimestamp, u64> as Timestamp>::Summary>>>,
+                                        frontier: &mut [CountMap<Product<RootTimestamp, u64>>]) -> () {
+        self.send_frontier(&mut frontier[0]);
+    }
+
+    fn push_external_progress(&mut self, external: &mut [CountMap<Product<RootTimestamp, u64>>]) -> () {
+        self.send_frontier(&mut external[0]);
+    }
+
+    fn pull_internal_progress(&mut self, internal: &mut [CountMap<Product<RootTimestamp, u64>>],
+                                         consumed: &mut [CountMap<Product<RootTimestamp, u64>>],
+                                         produced: &mut [CountMap<Product<RootTimestamp, u64>>]) -> bool
+    {
+        // forward input records to the program.
+        while let Some((time, data)) = self.input.pull() {
+            if data.len() > 0 {
+                let mut body = Vec::new();
+                body.write_u64::<LittleEndian>(time.inner).unwrap();
+                body.write_u64::<LittleEndian>(data.len() as u64).unwrap();
+                for record in data.drain_temp() {
+                    body.write_u64::<LittleEndian>(record.len() as u64).unwrap();
+                    body.write_all(&record[..]).unwrap();
+                }
+                if let Some(ref mut stdin) = self.stdin {
+                    write_frame(stdin, DATA, &body[..]).ok().expect("Command: write error");
+                }
+            }
+        }
+        if let Some(ref mut stdin) = self.stdin {
+            stdin.flush().ok().expect("Command: flush error");
+        }
+
+        // apply whatever the program has reported.
+        loop {
+            let frame = match self.frames.try_recv() {
+                Ok(frame)                       => frame,
+                Err(TryRecvError::Empty)        => break,
+                Err(TryRecvError::Disconnected) => {
+                    if self.held.len() > 0 {
+                        panic!("Command: program exited while holding capabilities {:?}", self.held.elements());
+                    }
+                    break;
+                },
+            };
+
+            let mut slice = &frame[..];
+            match slice.read_u8().ok() {
+                Some(DATA) => {
+                    let time = slice.read_u64::<LittleEndian>().ok().expect("Command: malformed data");
+                    let count = slice.read_u64::<LittleEndian>().ok().expect("Command: malformed data");
+                    let mut records = Vec::new();
+                    for _ in (0..count) {
+                        let length = slice.read_u64::<LittleEndian>().ok().expect("Command: malformed data") as usize;
+                        if length > slice.len() { panic!("Command: malformed data"); }
+                        let (record, rest) = slice.split_at(length);
+                        records.push(record.to_vec());
+                        slice = rest;
+                    }
+                    self.output.give_at(&RootTimestamp::new(time), records.into_iter());
+                },
+                Some(PROGRESS) => {
+                    for (time, delta) in read_changes(&mut slice).into_iter() {
+                        self.held.update(&time, delta);
+                        internal[0].update(&RootTimestamp::new(time), delta);
+                    }
+                },
+                _ => panic!("Command: unknown frame kind"),
+            }
+        }
+
+        self.input.pull_progress(&mut consumed[0]);
+        self.output.pull_progress(&mut produced[0]);
+
+        return false;   // capabilities announce any remaining work
+    }
+
+    fn notify_me(&self) -> bool { true }
+}
+
+impl<P: Pullable<(Product<RootTimestamp, u64>, Vec<Vec<u8>>)>> Drop for CommandScope<P> {
+    fn drop(&mut self) {
+        self.stdin = None;
+        let _ = self.process.wait();
+    }
+}
+
+fn write_frame<W: Write>(writer: &mut W, kind: u8, body: &[u8]) -> ::std::io::Result<()> {
+    try!(writer.write_u64::<LittleEndian>(body.len() as u64 + 1));
+    try!(writer.write_u8(kind));
+    writer.write_all(body)
+}
+
+// reads the kind and body of the next frame, or None if the reader ends or errors first.
+fn read_frame<R: Read>(reader: &mut R) -> Option<Vec<u8>> {
+    let length = match reader.read_u64::<LittleEndian>() {
+        Ok(length) if length > 0 => length as usize,
+        _ => return None,
+    };
+
+    let mut frame = vec![0u8; length];
+    let mut filled = 0;
+    while filled < length {
+        match reader.read(&mut frame[filled..]) {
+            Ok(0)       => return None,
+            Ok(read)    => filled += read,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => { },
+            Err(_)      => return None,
+        }
+    }
+    Some(frame)
+}
+
+fn read_changes(slice: &mut &[u8]) -> Vec<(u64, i64)> {
+    let count = slice.read_u64::<LittleEndian>().ok().expect("Command: malformed progress");
+    let mut changes = Vec::new();
+    for _ in (0..count) {
+        let time = slice.read_u64::<LittleEndian>().ok().expect("Command: malformed progress");
+        let delta = slice.read_i64::<LittleEndian>().ok().expect("Command: malformed progress");
+        changes.push((time, delta));
+    }
+    changes
+}
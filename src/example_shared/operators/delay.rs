@@ -13,28 +13,48 @@ pub trait DelayExt<G: GraphBuilder, D: Data> {
     fn delay<F: Fn(&D, &G::Timestamp)->G::Timestamp+'static>(&self, F) -> Self;
 }
 
+// records whose new time is not greater than their current time are sent immediately. others are
+// buffered, holding a notification for their new time; a buffer reaching the threshold is sent
+// right away under that capability, so that at most threshold records are held per time. buffers
+// are released as their times complete, in time order, as Notificator delivers notifications in
+// time order.
 impl<G: GraphBuilder, D: Data> DelayExt<G, D> for Stream<G, D>
 where G::Timestamp: Hash {
     fn delay<F: Fn(&D, &G::Timestamp)->G::Timestamp+'static>(&self, func: F) -> Stream<G, D> {
-        let _threshold = 256; // TODO : make more "streaming" by flushing
+        let threshold = 256;
         let mut elements = HashMap::new();
+        let mut immediate = Vec::new();
         self.unary_notify(Pipeline, format!("Delay"), vec![], move |input, output, notificator| {
             while let Some((time, data)) = input.pull() {
                 for datum in data.drain_temp() {
-                    let mut new_time = func(&datum, &time);
-                    if !(new_time >= time) {
-                        new_time = time;
+                    let new_time = func(&datum, &time);
+                    if new_time > time {
+                        if !elements.contains_key(&new_time) {
+                            notificator.notify_at(&new_time);
+                            elements.insert(new_time, Vec::new());
+                        }
+                        let buffer = elements.get_mut(&new_time).unwrap();
+                        buffer.push(datum);
+                        if buffer.len() >= threshold {
+                            output.give_at(&new_time, buffer.drain_temp());
+                        }
                     }
+                    else {
+                        immediate.push(datum);
+                    }
+                }
 
-                    elements.entry(new_time.clone())
-                            .or_insert_with(|| { notificator.notify_at(&new_time); Vec::new() })
-                            .push(datum);
+                if immediate.len() > 0 {
+                    output.give_at(&time, immediate.drain_temp());
                 }
             }
-            // for each available notification, send corresponding set
+
+            // for each available notification, send corresponding set; smaller times come first.
             while let Some((time, _count)) = notificator.next() {
                 if let Some(mut data) = elements.remove(&time) {
-                    output.give_at(&time, data.drain_temp());
+                    if data.len() > 0 {
+                        output.give_at(&time, data.drain_temp());
+                    }
                 }
             }
         })
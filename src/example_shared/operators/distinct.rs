@@ -1,38 +1,42 @@
-use std::collections::{HashMap, HashSet};
-use std::collections::hash_state::DefaultState;
-use std::hash::{hash, Hash, SipHasher};
-use std::default::Default;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
 
 use communication::*;
 use communication::pact::Exchange;
 
 use example_shared::*;
 use example_shared::operators::unary::UnaryNotifyExt;
+use example_shared::operators::hashing::hash_code;
 
 use columnar::Columnar;
 
+use drain::DrainExt;
+
 pub trait DistinctExtensionTrait {
     fn distinct(&self) -> Self;
     fn distinct_batch(&self) -> Self;
+    fn distinct_capped(&self, capacity: usize) -> Self;
 }
 
 impl<G: GraphBuilder, D: Data+Hash+Eq+Columnar> DistinctExtensionTrait for Stream<G, D>
 where G::Timestamp: Hash {
 
     fn distinct(&self) -> Stream<G, D> {
-        let mut elements: HashMap<_, HashSet<_, DefaultState<SipHasher>>> = HashMap::new();
-        let exch = Exchange::new(|x| hash::<_,SipHasher>(&x));
+        let mut elements: HashMap<_, HashSet<_>> = HashMap::new();
+        let exch = Exchange::new(|x: &D| hash_code(x));
         self.unary_notify(exch, format!("Distinct"), vec![], move |input, output, notificator| {
             while let Some((time, data)) = input.pull() {
-                let set = elements.entry(time).or_insert(Default::default());
+                if !elements.contains_key(&time) {
+                    elements.insert(time, HashSet::new());
+                    notificator.notify_at(&time);
+                }
+                let set = elements.get_mut(&time).unwrap();
                 let mut session = output.session(&time);
-                for datum in data.drain(..) {
+                for datum in data.drain_temp() {
                     if set.insert(datum.clone()) {
                         session.give(datum);
                     }
                 }
-
-                notificator.notify_at(&time);
             }
 
             while let Some((time, _count)) = notificator.next() {
@@ -42,19 +46,61 @@ where G::Timestamp: Hash {
     }
 
     fn distinct_batch(&self) -> Stream<G, D> {
-        let mut elements: HashMap<_, HashSet<_, DefaultState<SipHasher>>> = HashMap::new();
-        let exch = Exchange::new(|x| hash::<_,SipHasher>(&x));
+        let mut elements: HashMap<_, HashSet<_>> = HashMap::new();
+        let exch = Exchange::new(|x: &D| hash_code(x));
         self.unary_notify(exch, format!("DistinctBlock"), vec![], move |input, output, notificator| {
             while let Some((time, data)) = input.pull() {
-                let set = elements.entry(time).or_insert(Default::default());
-                for datum in data.drain(..) { set.insert(datum); }
+                if !elements.contains_key(&time) {
+                    elements.insert(time, HashSet::new());
+                    notificator.notify_at(&time);
+                }
+                let set = elements.get_mut(&time).unwrap();
+                for datum in data.drain_temp() { set.insert(datum); }
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some(data) = elements.remove(&time) {
+                    output.give_at(&time, data.into_iter());
+                }
+            }
+        })
+    }
 
-                notificator.notify_at(&time);
+    // suppresses records seen at any earlier completed time, remembering at most capacity records
+    // per worker. once full, the least recently admitted record is forgotten and may reappear.
+    // records are produced when their time completes, and times are processed as they complete.
+    fn distinct_capped(&self, capacity: usize) -> Stream<G, D> {
+        let mut elements: HashMap<_, HashSet<_>> = HashMap::new();
+        let mut seen = HashSet::new();
+        let mut order = VecDeque::new();
+        let exch = Exchange::new(|x: &D| hash_code(x));
+        self.unary_notify(exch, format!("DistinctCapped"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                if !elements.contains_key(&time) {
+                    elements.insert(time, HashSet::new());
+                    notificator.notify_at(&time);
+                }
+                let set = elements.get_mut(&time).unwrap();
+                for datum in data.drain_temp() { set.insert(datum); }
             }
 
             while let Some((time, _count)) = notificator.next() {
-                if let Some(mut data) = elements.remove(&time) {
-                    output.give_at(&time, data.drain());
+                if let Some(data) = elements.remove(&time) {
+                    let mut session = output.session(&time);
+                    for datum in data.into_iter() {
+                        if !seen.contains(&datum) {
+                            if capacity > 0 {
+                                seen.insert(datum.clone());
+                                order.push_back(datum.clone());
+                                while order.len() > capacity {
+                                    if let Some(oldest) = order.pop_front() {
+                                        seen.remove(&oldest);
+                                    }
+                                }
+                            }
+                            session.give(datum);
+                        }
+                    }
                 }
             }
         })
@@ -1,5 +1,7 @@
 use std::hash::Hash;
 use std::default::Default;
+use std::collections::HashMap;
+use std::mem;
 
 use std::rc::Rc;
 use std::cell::RefCell;
@@ -9,11 +11,16 @@ use progress::{Timestamp, CountMap};
 use progress::nested::subgraph::Source::{GraphInput, ScopeOutput};
 use progress::nested::subgraph::Target::{GraphOutput, ScopeInput};
 use progress::nested::product::Product;
+use progress::timestamp::RootTimestamp;
 use communication::*;
 use communication::channels::ObserverHelper;
 
+use communication::pact::Pipeline;
+
 use example_shared::*;
-use example_shared::operators::delay::*;
+use example_shared::operators::unary::UnaryStreamExt;
+
+use drain::DrainExt;
 
 pub trait EnterSubgraphExt<G: GraphBuilder, T: Timestamp, D: Data> {
     fn enter(&self, &Stream<G, D>) -> Stream<SubgraphBuilder<G, T>, D>;
@@ -23,11 +30,27 @@ pub trait EnterSubgraphAtExt<G: GraphBuilder, T: Timestamp, D: Data>  where  G::
     fn enter_at<F:Fn(&D)->T+'static>(&self, stream: &Stream<G, D>, initial: F) -> Stream<SubgraphBuilder<G, T>, D> ;
 }
 
-impl<G: GraphBuilder, T: Timestamp, D: Data, E: EnterSubgraphExt<G, T, D>> EnterSubgraphAtExt<G, T, D> for E
+// assigns each record its inner timestamp as it enters, without buffering beyond each batch.
+impl<G: GraphBuilder, T: Timestamp, D: Data> EnterSubgraphAtExt<G, T, D> for SubgraphBuilder<G, T>
 where G::Timestamp: Hash, T: Hash {
     fn enter_at<F:Fn(&D)->T+'static>(&self, stream: &Stream<G, D>, initial: F) ->
         Stream<SubgraphBuilder<G, T>, D> {
-            self.enter(stream).delay(move |datum, time| Product::new(time.outer, initial(datum)))
+
+        let (targets, registrar) = OutputPort::<Product<G::Timestamp, T>, D>::new();
+        let produced = Rc::new(RefCell::new(CountMap::new()));
+        let ingress = EnterAtNub {
+            targets: ObserverHelper::new(targets, produced.clone()),
+            initial: initial,
+            buffer:  HashMap::new(),
+        };
+
+        let scope_index = self.subgraph.borrow().index;
+        let input_index = self.subgraph.borrow_mut().new_input(produced);
+
+        if self.is_member() { stream.connect_to(ScopeInput(scope_index, input_index), ingress); }
+        else                { stream.connect_to(ScopeInput(scope_index, input_index), NonMemberNub::new(self.parent.index())); }
+
+        Stream::new(GraphInput(input_index), registrar, self.clone())
     }
 }
 
@@ -42,7 +65,8 @@ EnterSubgraphExt<G, T, D> for SubgraphBuilder<G, T> {
         let scope_index = self.subgraph.borrow().index;
         let input_index = self.subgraph.borrow_mut().new_input(produced);
 
-        stream.connect_to(ScopeInput(scope_index, input_index), ingress);
+        if self.is_member() { stream.connect_to(ScopeInput(scope_index, input_index), ingress); }
+        else                { stream.connect_to(ScopeInput(scope_index, input_index), NonMemberNub::new(self.parent.index())); }
         // self.parent.add_edge(stream.name, ScopeInput(scope_index, input_index));
         // stream.ports.add_observer(ingress);
 
@@ -70,6 +94,76 @@ impl<G: GraphBuilder, D: Data, T: Timestamp> LeaveSubgraphExt<G, D> for Stream<S
 }
 
 
+pub trait LeaveSubgraphAtExt<G: GraphBuilder, T: Timestamp, D: Data> {
+    fn leave_at<F: Fn(&Product<G::Timestamp, T>)->G::Timestamp+'static>(&self, func: F) -> Stream<G, D>;
+}
+
+// leaves the subgraph with outer timestamps determined by func, rather than by dropping the inner
+// coordinate. func(time) should be greater or equal to time.outer; smaller results are advanced to
+// time.outer, as records cannot be sent back in time, and incomparable results panic.
+impl<G: GraphBuilder, T: Timestamp, D: Data> LeaveSubgraphAtExt<G, T, D> for Stream<SubgraphBuilder<G, T>, D> {
+    fn leave_at<F: Fn(&Product<G::Timestamp, T>)->G::Timestamp+'static>(&self, func: F) -> Stream<G, D> {
+        // records are moved to (func(time), time.inner), which leaves at func(time).
+        self.unary_stream(Pipeline, format!("LeaveAt"), move |input, output| {
+                while let Some((time, data)) = input.pull() {
+                    let outer = func(&time);
+                    let outer = if time.outer.le(&outer) { outer }
+                                else if outer.le(&time.outer) { time.outer }
+                                else { panic!("leave_at: {:?} is incomparable with {:?}", outer, time.outer) };
+                    output.give_at(&Product::new(outer, time.inner), data.drain_temp());
+                }
+            })
+            .leave()
+    }
+}
+
+
+// stands in for the ingress of a subgraph on a worker that does not coordinate it, and so never runs
+// it; records given to it would never be consumed, and are refused rather than lost.
+pub struct NonMemberNub<T: Timestamp, TData: Data> {
+    worker:  u64,
+    phantom: PhantomData<(T, TData)>,
+}
+
+impl<T: Timestamp, TData: Data> NonMemberNub<T, TData> {
+    pub fn new(worker: u64) -> NonMemberNub<T, TData> { NonMemberNub { worker: worker, phantom: PhantomData } }
+}
+
+impl<T: Timestamp, TData: Data> Observer for NonMemberNub<T, TData> {
+    type Time = T;
+    type Data = TData;
+    #[inline(always)] fn open(&mut self, _time: &T) -> () { }
+    #[inline(always)] fn show(&mut self, data: &TData) { self.give(data.clone()); }
+    #[inline(always)] fn give(&mut self, _data: TData) {
+        panic!("enter: worker {} sent records into a subcomputation_among it is not a member of", self.worker);
+    }
+    #[inline(always)] fn shut(&mut self, _time: &T) -> () { }
+}
+
+pub struct EnterAtNub<TOuter: Timestamp, TInner: Timestamp+Hash, TData: Data, F: Fn(&TData)->TInner> {
+    targets: ObserverHelper<OutputPort<Product<TOuter, TInner>, TData>>,
+    initial: F,
+    buffer:  HashMap<TInner, Vec<TData>>,
+}
+
+impl<TOuter, TInner, TData, F> Observer for EnterAtNub<TOuter, TInner, TData, F>
+where TOuter: Timestamp, TInner: Timestamp+Hash, TData: Data, F: Fn(&TData)->TInner {
+    type Time = TOuter;
+    type Data = TData;
+    #[inline(always)] fn open(&mut self, _time: &TOuter) -> () { }
+    #[inline(always)] fn show(&mut self, data: &TData) { self.give(data.clone()); }
+    #[inline(always)] fn give(&mut self, data:  TData) {
+        let inner = (self.initial)(&data);
+        self.buffer.entry(inner).or_insert(Vec::new()).push(data);
+    }
+    #[inline(always)] fn shut(&mut self, time: &TOuter) -> () {
+        for (inner, mut data) in mem::replace(&mut self.buffer, HashMap::new()).into_iter() {
+            self.targets.give_at(&Product::new(time.clone(), inner), data.drain_temp());
+        }
+    }
+}
+
+
 pub struct IngressNub<TOuter: Timestamp, TInner: Timestamp, TData: Data> {
     targets: ObserverHelper<OutputPort<Product<TOuter, TInner>, TData>>,
 }
@@ -98,3 +192,65 @@ where TOuter: Timestamp, TInner: Timestamp, TData: Data {
     #[inline(always)] fn give(&mut self, data:  TData) { self.targets.give(data); }
     #[inline(always)] fn shut(&mut self, time: &Product<TOuter, TInner>) { self.targets.shut(&time.outer); }
 }
+
+
+pub trait EnterRegionExt<G: GraphBuilder, D: Data> {
+    fn enter_region(&self, &Stream<G, D>) -> Stream<RegionBuilder<G>, D>;
+}
+
+impl<G: GraphBuilder, D: Data> EnterRegionExt<G, D> for RegionBuilder<G> {
+    fn enter_region(&self, stream: &Stream<G, D>) -> Stream<RegionBuilder<G>, D> {
+
+        let (targets, registrar) = OutputPort::<G::Timestamp, D>::new();
+        let produced = Rc::new(RefCell::new(CountMap::new()));
+        let ingress = RegionIngressNub { targets: targets, counts: produced.clone(), count: 0 };
+
+        let scope_index = self.subgraph.borrow().index;
+        let input_index = self.subgraph.borrow_mut().new_input(produced);
+
+        stream.connect_to(ScopeInput(scope_index, input_index), ingress);
+
+        Stream::new(GraphInput(input_index), registrar, self.clone())
+    }
+}
+
+pub trait LeaveRegionExt<G: GraphBuilder, D: Data> {
+    fn leave_region(&self) -> Stream<G, D>;
+}
+
+impl<G: GraphBuilder, D: Data> LeaveRegionExt<G, D> for Stream<RegionBuilder<G>, D> {
+    fn leave_region(&self) -> Stream<G, D> {
+
+        let builder = self.builder();
+
+        let output_index = builder.subgraph.borrow_mut().new_output();
+        let (targets, registrar) = OutputPort::<G::Timestamp, D>::new();
+        self.connect_to(GraphOutput(output_index), targets);
+        let subgraph_index = builder.subgraph.borrow().index;
+        Stream::new(ScopeOutput(subgraph_index, output_index),
+                    registrar,
+                    builder.parent.clone())
+    }
+}
+
+
+// records cross into a region unchanged; only the counts reported to the region carry its (trivial)
+// inner coordinate.
+pub struct RegionIngressNub<T: Timestamp, TData: Data> {
+    targets: OutputPort<T, TData>,
+    counts:  Rc<RefCell<CountMap<Product<T, RootTimestamp>>>>,
+    count:   i64,
+}
+
+impl<T: Timestamp, TData: Data> Observer for RegionIngressNub<T, TData> {
+    type Time = T;
+    type Data = TData;
+    #[inline(always)] fn open(&mut self, time: &T) -> () { self.targets.open(time); }
+    #[inline(always)] fn show(&mut self, data: &TData) { self.count += 1; self.targets.show(data); }
+    #[inline(always)] fn give(&mut self, data:  TData) { self.count += 1; self.targets.give(data); }
+    #[inline(always)] fn shut(&mut self, time: &T) -> () {
+        self.counts.borrow_mut().update(&Product::new(time.clone(), RootTimestamp), self.count);
+        self.targets.shut(time);
+        self.count = 0;
+    }
+}
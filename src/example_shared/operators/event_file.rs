@@ -0,0 +1,308 @@
+use std::io::{Read, Write, Error, ErrorKind};
+use std::io::Result as IoResult;
+use std::marker::PhantomData;
+use std::default::Default;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+use columnar::{Columnar, ColumnarStack};
+
+use progress::Timestamp;
+use progress::count_map::CountMap;
+use progress::timestamp::RootTimestamp;
+use progress::nested::product::Product;
+use communication::{Data, Pushable, Pullable};
+
+use example_shared::operators::capture::Event;
+
+// A file of captured events starts with a header of little-endian u64s: a magic number, the format
+// version, the index of the worker that wrote it, and the length of a tag naming the timestamp and
+// data types, followed by the tag's bytes. Each event follows as a u64 length, then that many
+// bytes: a kind byte (0 for progress, 1 for messages) and the event's parts, each a u64 length and
+// a Columnar encoding.
+//
+// The tag is derived from the types by EventTag, for example "Product(Root,u64)/(u64,String)", and
+// checked by the reader. A file whose final event is incomplete, as after a crash, reads as if it
+// ended before that event, and reports that it was truncated.
+
+const MAGIC: u64 = 0x5354_4e45_5645_4454;   // "TDEVENTS"
+const VERSION: u64 = 2;
+
+const PROGRESS: u8 = 0;
+const MESSAGES: u8 = 1;
+
+// names a type in the header of an event file. the name must be non-empty, and should be distinct
+// from the names of types with different encodings, as files are only read as the type they name.
+pub trait EventTag {
+    fn tag() -> String;
+}
+
+impl EventTag for u8 { fn tag() -> String { format!("u8") } }
+impl EventTag for u16 { fn tag() -> String { format!("u16") } }
+impl EventTag for u32 { fn tag() -> String { format!("u32") } }
+impl EventTag for u64 { fn tag() -> String { format!("u64") } }
+impl EventTag for i8 { fn tag() -> String { format!("i8") } }
+impl EventTag for i16 { fn tag() -> String { format!("i16") } }
+impl EventTag for i32 { fn tag() -> String { format!("i32") } }
+impl EventTag for i64 { fn tag() -> String { format!("i64") } }
+impl EventTag for String { fn tag() -> String { format!("String") } }
+impl<T: EventTag> EventTag for Vec<T> { fn tag() -> String { format!("Vec({})", T::tag()) } }
+impl<T: EventTag> EventTag for Option<T> { fn tag() -> String { format!("Option({})", T::tag()) } }
+impl<A: EventTag, B: EventTag> EventTag for (A, B) {
+    fn tag() -> String { format!("({},{})", A::tag(), B::tag()) }
+}
+impl<A: EventTag, B: EventTag, C: EventTag> EventTag for (A, B, C) {
+    fn tag() -> String { format!("({},{},{})", A::tag(), B::tag(), C::tag()) }
+}
+impl EventTag for RootTimestamp { fn tag() -> String { format!("Root") } }
+impl<TOuter: EventTag, TInner: EventTag> EventTag for Product<TOuter, TInner> {
+    fn tag() -> String { format!("Product({},{})", TOuter::tag(), TInner::tag()) }
+}
+
+// the tag of files of events with timestamp T and data D.
+fn event_tag<T: EventTag, D: EventTag>() -> String {
+    let (time, data) = (T::tag(), D::tag());
+    assert!(time.len() > 0 && data.len() > 0, "EventTag: types must have non-empty tags");
+    format!("{}/{}", time, data)
+}
+
+// writes events to a Write, for example a BufWriter<File>; usable with capture_into.
+pub struct EventWriter<T: Timestamp+EventTag, D: Data+Columnar+EventTag, W: Write> {
+    writer:     W,
+    buffer:     Vec<u8>,
+    phantom:    PhantomData<(T, D)>,
+}
+
+impl<T: Timestamp+EventTag, D: Data+Columnar+EventTag, W: Write> EventWriter<T, D, W> {
+    pub fn new(mut writer: W, worker: u64) -> IoResult<EventWriter<T, D, W>> {
+        let tag = event_tag::<T, D>();
+        try!(writer.write_u64::<LittleEndian>(MAGIC));
+        try!(writer.write_u64::<LittleEndian>(VERSION));
+        try!(writer.write_u64::<LittleEndian>(worker));
+        try!(writer.write_u64::<LittleEndian>(tag.len() as u64));
+        try!(writer.write_all(tag.as_bytes()));
+        Ok(EventWriter {
+            writer:     writer,
+            buffer:     Vec::new(),
+            phantom:    PhantomData,
+        })
+    }
+
+    // writes the event, and flushes the writer after progress so that the file describes whole times.
+    pub fn write_event(&mut self, event: Event<T, D>) -> IoResult<()> {
+        self.buffer.clear();
+        let kind = match event {
+            Event::Progress(changes) => {
+                let mut times: <T as Columnar>::Stack = Default::default();
+                let mut deltas = Vec::new();
+                for (time, delta) in changes.into_iter() {
+                    times.push(time);
+                    try!(deltas.write_i64::<LittleEndian>(delta));
+                }
+                try!(encode_part::<T>(&mut times, &mut self.buffer));
+                try!(self.buffer.write_u64::<LittleEndian>(deltas.len() as u64));
+                try!(self.buffer.write_all(&deltas[..]));
+                PROGRESS
+            },
+            Event::Messages(time, data) => {
+                let mut times: <T as Columnar>::Stack = Default::default();
+                let mut records: <D as Columnar>::Stack = Default::default();
+                times.push(time);
+                for datum in data.into_iter() { records.push(datum); }
+                try!(encode_part::<T>(&mut times, &mut self.buffer));
+                try!(encode_part::<D>(&mut records, &mut self.buffer));
+                MESSAGES
+            },
+        };
+
+        try!(self.writer.write_u64::<LittleEndian>(self.buffer.len() as u64 + 1));
+        try!(self.writer.write_u8(kind));
+        try!(self.writer.write_all(&self.buffer[..]));
+        if kind == PROGRESS { try!(self.writer.flush()); }
+        Ok(())
+    }
+}
+
+impl<T: Timestamp+EventTag, D: Data+Columnar+EventTag, W: Write> Pushable<Event<T, D>> for EventWriter<T, D, W> {
+    fn push(&mut self, event: Event<T, D>) {
+        self.write_event(event).ok().expect("EventWriter: write error");
+    }
+}
+
+// reads events from a Read, for example a BufReader<File>; usable with replay. once the events run
+// out the reader stops, leaving the frontier where the events left it, unless finish() was called.
+pub struct EventReader<T: Timestamp+EventTag, D: Data+Columnar+EventTag, R: Read> {
+    reader:     R,
+    worker:     u64,
+    frontier:   CountMap<T>,    // frontier described by the events read so far
+    finish:     bool,           // close the frontier once the events run out
+    done:       bool,
+    truncated:  bool,
+    phantom:    PhantomData<D>,
+}
+
+impl<T: Timestamp+EventTag, D: Data+Columnar+EventTag, R: Read> EventReader<T, D, R> {
+    // reads the header, checking that the file was written for the same timestamp and data types.
+    pub fn new(mut reader: R) -> IoResult<EventReader<T, D, R>> {
+        let tag = event_tag::<T, D>();
+        let magic = try!(reader.read_u64::<LittleEndian>());
+        if magic != MAGIC {
+            return Err(Error::new(ErrorKind::InvalidInput, "EventReader: not an event file"));
+        }
+        let version = try!(reader.read_u64::<LittleEndian>());
+        if version != VERSION {
+            return Err(Error::new(ErrorKind::InvalidInput, "EventReader: unsupported version"));
+        }
+        let worker = try!(reader.read_u64::<LittleEndian>());
+        let length = try!(reader.read_u64::<LittleEndian>());
+        if length != tag.len() as u64 {
+            return Err(Error::new(ErrorKind::InvalidInput, "EventReader: timestamp or data type mismatch"));
+        }
+        let mut bytes = vec![0u8; tag.len()];
+        if read_fully(&mut reader, &mut bytes[..]) < bytes.len() {
+            return Err(Error::new(ErrorKind::InvalidInput, "EventReader: truncated header"));
+        }
+        if &bytes[..] != tag.as_bytes() {
+            return Err(Error::new(ErrorKind::InvalidInput, "EventReader: timestamp or data type mismatch"));
+        }
+
+        Ok(EventReader {
+            reader:     reader,
+            worker:     worker,
+            frontier:   CountMap::new_from(&Default::default(), 1),
+            finish:     false,
+            done:       false,
+            truncated:  false,
+            phantom:    PhantomData,
+        })
+    }
+
+    // the index of the worker that wrote the events.
+    pub fn worker(&self) -> u64 { self.worker }
+
+    // once the events run out, report the remaining frontier as complete so that replay can finish.
+    // only appropriate if the file is known to be complete; a truncated file is never finished.
+    pub fn finish(&mut self) { self.finish = true; }
+
+    // true if the events ran out part way through an event, or at a malformed one.
+    pub fn truncated(&self) -> bool { self.truncated }
+
+    // reads the next event, or None if there is no further complete event, in which case truncated
+    // is set unless the events ended cleanly.
+    fn read_event(&mut self) -> Option<Event<T, D>> {
+        let mut header = [0u8; 8];
+        match read_fully(&mut self.reader, &mut header[..]) {
+            0 => return None,
+            8 => { },
+            _ => { self.truncated = true; return None; },
+        }
+        let length = (&header[..]).read_u64::<LittleEndian>().unwrap();
+        if length == 0 { self.truncated = true; return None; }
+
+        // the buffer grows only as bytes arrive, so a corrupt length reads to the end of the file
+        // and reports truncation, rather than allocating whatever the length claims.
+        let mut bytes = Vec::new();
+        if self.reader.by_ref().take(length).read_to_end(&mut bytes).is_err() || (bytes.len() as u64) < length {
+            self.truncated = true;
+            return None;
+        }
+
+        let event = self.decode_event(&bytes[..]);
+        if event.is_none() { self.truncated = true; }
+        event
+    }
+
+    // decodes the kind byte and parts of an event, or None if they are malformed.
+    fn decode_event(&self, bytes: &[u8]) -> Option<Event<T, D>> {
+        let mut slice = &bytes[1..];
+        match bytes[0] {
+            PROGRESS => {
+                let times = match decode_part::<T>(&mut slice) { Some(times) => times, None => return None };
+                let count = match slice.read_u64::<LittleEndian>() { Ok(count) => count, Err(_) => return None };
+                if count != 8 * times.len() as u64 { return None; }
+                let mut changes = Vec::new();
+                for time in times.into_iter() {
+                    match slice.read_i64::<LittleEndian>() {
+                        Ok(delta) => changes.push((time, delta)),
+                        Err(_)    => return None,
+                    }
+                }
+                Some(Event::Progress(changes))
+            },
+            MESSAGES => {
+                let mut times = match decode_part::<T>(&mut slice) { Some(times) => times, None => return None };
+                let data = match decode_part::<D>(&mut slice) { Some(data) => data, None => return None };
+                match times.pop() {
+                    Some(time) => Some(Event::Messages(time, data)),
+                    None       => None,
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<T: Timestamp+EventTag, D: Data+Columnar+EventTag, R: Read> Pullable<Event<T, D>> for EventReader<T, D, R> {
+    fn pull(&mut self) -> Option<Event<T, D>> {
+        if self.done { return None; }
+
+        if let Some(event) = self.read_event() {
+            if let Event::Progress(ref changes) = event {
+                for &(ref time, delta) in changes.iter() {
+                    self.frontier.update(time, delta);
+                }
+            }
+            Some(event)
+        }
+        else {
+            self.done = true;
+            if self.finish && !self.truncated {
+                let mut closing = Vec::new();
+                while let Some((time, delta)) = self.frontier.pop() {
+                    closing.push((time, -delta));
+                }
+                if closing.len() > 0 { Some(Event::Progress(closing)) } else { None }
+            }
+            else { None }
+        }
+    }
+}
+
+// appends the encoding of the stack, preceded by its length.
+fn encode_part<X: Columnar>(stack: &mut <X as Columnar>::Stack, buffer: &mut Vec<u8>) -> IoResult<()> {
+    let mut bytes = Vec::new();
+    try!(stack.encode(&mut bytes));
+    try!(buffer.write_u64::<LittleEndian>(bytes.len() as u64));
+    buffer.write_all(&bytes[..])
+}
+
+// decodes a part written by encode_part, advancing the slice past it.
+fn decode_part<X: Columnar>(slice: &mut &[u8]) -> Option<Vec<X>> {
+    let length = match slice.read_u64::<LittleEndian>() { Ok(length) => length as usize, Err(_) => return None };
+    if length > slice.len() { return None; }
+
+    let (part, rest) = slice.split_at(length);
+    *slice = rest;
+
+    let mut stack: <X as Columnar>::Stack = Default::default();
+    if stack.decode(&mut &part[..]).is_err() { return None; }
+
+    let mut result = Vec::new();
+    while let Some(item) = stack.pop() { result.push(item); }
+    result.reverse();   // the stack pops items in the reverse of the order they were pushed
+    Some(result)
+}
+
+// fills buffer from reader, returning the number of bytes read before it was full, or the reader
+// ended or errored.
+fn read_fully<R: Read>(reader: &mut R, buffer: &mut [u8]) -> usize {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0)       => return filled,
+            Ok(read)    => filled += read,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => { },
+            Err(_)      => return filled,
+        }
+    }
+    filled
+}
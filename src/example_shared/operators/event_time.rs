@@ -0,0 +1,18 @@
+use progress::Timestamp;
+use progress::nested::product::Product;
+
+// Timestamps whose (innermost) coordinate is a u64 event time.
+pub trait EventTime : Timestamp {
+    fn event_time(&self) -> u64;
+    fn with_event_time(&self, time: u64) -> Self;   // same timestamp, with event time replaced
+}
+
+impl EventTime for u64 {
+    fn event_time(&self) -> u64 { *self }
+    fn with_event_time(&self, time: u64) -> u64 { time }
+}
+
+impl<TOuter: Timestamp> EventTime for Product<TOuter, u64> {
+    fn event_time(&self) -> u64 { self.inner }
+    fn with_event_time(&self, time: u64) -> Product<TOuter, u64> { Product::new(self.outer, time) }
+}
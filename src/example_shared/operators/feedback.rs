@@ -15,41 +15,53 @@ use example_shared::*;
 // use example_shared::operators::unary::UnaryStreamExt;
 
 pub trait FeedbackExt<G: GraphBuilder> {
+    // records at times not less or equal to limit are dropped rather than fed back.
     fn loop_variable<D:Data>(&self, limit: G::Timestamp, summary: <G::Timestamp as Timestamp>::Summary)
         -> (FeedbackHelper<ObserverHelper<FeedbackObserver<G::Timestamp, D>>>, Stream<G, D>);
+
+    // all records are fed back. the loop runs until a round sends no records around the cycle, at
+    // which point the progress tracker sees no further work at the outer time.
+    fn fixpoint_variable<D:Data>(&self, summary: <G::Timestamp as Timestamp>::Summary)
+        -> (FeedbackHelper<ObserverHelper<FeedbackObserver<G::Timestamp, D>>>, Stream<G, D>);
 }
 
 impl<G: GraphBuilder> FeedbackExt<G> for G {
     fn loop_variable<D:Data>(&self, limit: G::Timestamp, summary: <G::Timestamp as Timestamp>::Summary)
         -> (FeedbackHelper<ObserverHelper<FeedbackObserver<G::Timestamp, D>>>, Stream<G, D>) {
+        new_feedback(self, Some(limit), summary)
+    }
 
-        let (targets, registrar) = OutputPort::<G::Timestamp, D>::new();
-        let produced: Rc<RefCell<CountMap<G::Timestamp>>> = Default::default();
-        let consumed: Rc<RefCell<CountMap<G::Timestamp>>> = Default::default();
+    fn fixpoint_variable<D:Data>(&self, summary: <G::Timestamp as Timestamp>::Summary)
+        -> (FeedbackHelper<ObserverHelper<FeedbackObserver<G::Timestamp, D>>>, Stream<G, D>) {
+        new_feedback(self, None, summary)
+    }
+}
 
-        let feedback_output = ObserverHelper::new(targets, produced.clone());
-        let feedback_input =  ObserverHelper::new(FeedbackObserver {
-            limit: limit, summary: summary, targets: feedback_output, active: false
-        }, consumed.clone());
+fn new_feedback<G: GraphBuilder, D: Data>(builder: &G, limit: Option<G::Timestamp>, summary: <G::Timestamp as Timestamp>::Summary)
+    -> (FeedbackHelper<ObserverHelper<FeedbackObserver<G::Timestamp, D>>>, Stream<G, D>) {
 
-        let index = self.add_scope(FeedbackScope {
-            consumed_messages:  consumed.clone(),
-            produced_messages:  produced.clone(),
-            summary:            summary,
-        });
+    let (targets, registrar) = OutputPort::<G::Timestamp, D>::new();
+    let produced: Rc<RefCell<CountMap<G::Timestamp>>> = Default::default();
+    let consumed: Rc<RefCell<CountMap<G::Timestamp>>> = Default::default();
 
-        let helper = FeedbackHelper {
-            index:  index,
-            target: feedback_input,
-        };
+    let feedback_output = ObserverHelper::new(targets, produced.clone());
+    let feedback_input =  ObserverHelper::new(FeedbackObserver {
+        limit: limit, summary: summary, targets: feedback_output, active: false
+    }, consumed.clone());
 
-        (helper, Stream::new(ScopeOutput(index, 0), registrar, self.clone()))
-    }
+    let index = builder.add_scope(FeedbackScope::new(consumed.clone(), produced.clone(), summary));
+
+    let helper = FeedbackHelper {
+        index:  index,
+        target: feedback_input,
+    };
+
+    (helper, Stream::new(ScopeOutput(index, 0), registrar, builder.clone()))
 }
 
 // implementation of the feedback vertex, essentially, as an observer
 pub struct FeedbackObserver<T: Timestamp, D:Data> {
-    limit:      T,
+    limit:      Option<T>,  // None feeds back all records
     summary:    T::Summary,
     targets:    ObserverHelper<OutputPort<T, D>>,
     active:     bool,
@@ -59,7 +71,10 @@ impl<T: Timestamp, D: Data> Observer for FeedbackObserver<T, D> {
     type Time = T;
     type Data = D;
     #[inline(always)] fn open(&mut self, time: &T) {
-        self.active = time.le(&self.limit); // don't send if not less than limit
+        self.active = match self.limit {    // don't send if not less than limit
+            Some(ref limit) => time.le(limit),
+            None            => true,
+        };
         if self.active { self.targets.open(&self.summary.results_in(time)); }
     }
     #[inline(always)] fn show(&mut self, data: &D) { if self.active { self.targets.show(data); } }
@@ -100,6 +115,16 @@ pub struct FeedbackScope<T:Timestamp> {
 }
 
 
+impl<T:Timestamp> FeedbackScope<T> {
+    pub fn new(consumed: Rc<RefCell<CountMap<T>>>, produced: Rc<RefCell<CountMap<T>>>, summary: T::Summary) -> FeedbackScope<T> {
+        FeedbackScope {
+            consumed_messages:  consumed,
+            produced_messages:  produced,
+            summary:            summary,
+        }
+    }
+}
+
 impl<T:Timestamp> Scope<T> for FeedbackScope<T> {
     fn name(&self) -> String { format!("Feedback") }
     fn inputs(&self) -> u64 { 1 }
@@ -0,0 +1,204 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::default::Default;
+
+use progress::{Scope, Antichain};
+use progress::nested::subgraph::Source::ScopeOutput;
+use progress::count_map::CountMap;
+
+use communication::*;
+use communication::channels::ObserverHelper;
+
+use example_shared::*;
+use example_shared::operators::event_time::EventTime;
+
+use drain::DrainExt;
+
+// the number of lines read each time the source is scheduled.
+const BATCH: usize = 1024;
+
+// Where lines come from. Files are assigned to workers round-robin by position in the list; a
+// single split file is divided into equal byte ranges, one per worker, with each line read by the
+// worker whose range contains its first byte.
+pub enum LineParts {
+    Files(Vec<PathBuf>),
+    Split(PathBuf),
+}
+
+// The event time under which each line is produced: the position of its file in the list, or the
+// number of lines this worker read before it, divided by the given count, which must be positive.
+pub enum LineEpochs {
+    PerFile,
+    PerLines(u64),
+}
+
+pub trait LineSourceExt<G: GraphBuilder> where G::Timestamp: EventTime {
+    fn read_lines(&self, parts: LineParts, epochs: LineEpochs) -> Stream<G, String>;
+}
+
+impl<G: GraphBuilder> LineSourceExt<G> for G where G::Timestamp: EventTime {
+    fn read_lines(&self, parts: LineParts, epochs: LineEpochs) -> Stream<G, String> {
+
+        if let LineEpochs::PerLines(0) = epochs {
+            panic!("read_lines: PerLines requires a positive number of lines per epoch");
+        }
+
+        let index = self.index();
+        let peers = self.peers();
+
+        let mut assigned = Vec::new();
+        match parts {
+            LineParts::Files(paths) => {
+                for (position, path) in paths.into_iter().enumerate() {
+                    if position as u64 % peers == index {
+                        assigned.push(LinePart { path: path, file: position as u64, start: 0, end: None });
+                    }
+                }
+            },
+            LineParts::Split(path) => {
+                let length = File::open(&path).and_then(|file| file.metadata())
+                                              .ok().expect("read_lines: could not open file")
+                                              .len();
+                let start = length * index / peers;
+                let end = length * (index + 1) / peers;
+                assigned.push(LinePart { path: path, file: 0, start: start, end: Some(end) });
+            },
+        }
+        assigned.reverse();     // parts are taken from the back
+
+        let (targets, registrar) = OutputPort::<G::Timestamp, String>::new();
+        let produced = Default::default();
+
+        let scope_index = self.add_scope(LineSourceScope {
+            parts:      assigned,
+            current:    None,
+            epochs:     epochs,
+            lines:      0,
+            held:       Some(Default::default()),
+            buffer:     Vec::new(),
+            output:     ObserverHelper::new(targets, produced),
+            copies:     peers,
+        });
+
+        Stream::new(ScopeOutput(scope_index, 0), registrar, self.clone())
+    }
+}
+
+struct LinePart {
+    path:   PathBuf,
+    file:   u64,            // position in the list of files
+    start:  u64,            // first byte of the range
+    end:    Option<u64>,    // lines starting at or after end belong to another worker
+}
+
+// holds a capability for the event time of the lines it is reading, advancing it as the event time
+// advances, and releasing it once all of its parts have been read. as with other sources, the
+// initial capability is reported once for each peer, and each worker releases its own share.
+pub struct LineSourceScope<T: EventTime> {
+    parts:      Vec<LinePart>,
+    current:    Option<(LinePart, BufReader<File>, u64)>,   // part being read and the position in it
+    epochs:     LineEpochs,
+    lines:      u64,
+    held:       Option<T>,
+    buffer:     Vec<String>,
+    output:     ObserverHelper<OutputPort<T, String>>,
+    copies:     u64,
+}
+
+impl<T: EventTime> LineSourceScope<T> {
+    // opens the next part, positioned at the first line starting in its range.
+    fn open_next(&mut self) -> bool {
+        if let Some(part) = self.parts.pop() {
+            let file = File::open(&part.path).ok().expect("read_lines: could not open file");
+            let mut reader = BufReader::new(file);
+            let mut position = part.start;
+            if part.start > 0 {
+                // the line containing byte start - 1 belongs to the previous range.
+                reader.seek(SeekFrom::Start(part.start - 1)).ok().expect("read_lines: could not seek");
+                let mut skipped = String::new();
+                let read = reader.read_line(&mut skipped).ok().expect("read_lines: could not read");
+                position = part.start - 1 + read as u64;
+            }
+            self.current = Some((part, reader, position));
+            true
+        }
+        else { false }
+    }
+
+    // sends buffered lines at the held time.
+    fn flush(&mut self) {
+        if self.buffer.len() > 0 {
+            if let Some(time) = self.held {
+                self.output.give_at(&time, self.buffer.drain_temp());
+            }
+        }
+    }
+
+    // moves the held capability to next, after sending lines at the current time.
+    fn hold(&mut self, next: Option<T>, internal: &mut CountMap<T>) {
+        if self.held != next {
+            self.flush();
+            if let Some(ref time) = next { internal.update(time, 1); }
+            if let Some(ref time) = self.held { internal.update(time, -1); }
+            self.held = next;
+        }
+    }
+}
+
+impl<T: EventTime> Scope<T> for LineSourceScope<T> {
+    fn name(&self) -> String { format!("ReadLines") }
+    fn inputs(&self) -> u64 { 0 }
+    fn outputs(&self) -> u64 { 1 }
+
+    fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<T::Summary>>>, Vec<CountMap<T>>) {
+        (Vec::new(), vec![CountMap::new_from(&Default::default(), self.copies as i64)])
+    }
+
+    fn pull_internal_progress(&mut self, internal: &mut [CountMap<T>],
+                                        _consumed: &mut [CountMap<T>],
+                                         produced: &mut [CountMap<T>]) -> bool
+    {
+        let mut read = 0;
+        while read < BATCH && self.held.is_some() {
+            if self.current.is_none() && !self.open_next() {
+                self.hold(None, &mut internal[0]);
+            }
+            else {
+                let mut line = String::new();
+                let (file, length) = {
+                    let &mut (ref part, ref mut reader, ref mut position) = self.current.as_mut().unwrap();
+                    let in_range = match part.end { Some(end) => *position < end, None => true };
+                    let length = if in_range { reader.read_line(&mut line).ok().expect("read_lines: could not read") } else { 0 };
+                    *position += length as u64;
+                    (part.file, length)
+                };
+
+                if length == 0 {
+                    self.current = None;
+                }
+                else {
+                    if line.ends_with("\n") { line.pop(); }
+                    if line.ends_with("\r") { line.pop(); }
+
+                    let epoch = match self.epochs {
+                        LineEpochs::PerFile         => file,
+                        LineEpochs::PerLines(count) => self.lines / count,
+                    };
+                    let time: T = Default::default();
+                    self.hold(Some(time.with_event_time(epoch)), &mut internal[0]);
+                    self.buffer.push(line);
+                    self.lines += 1;
+                    read += 1;
+                }
+            }
+        }
+
+        self.flush();
+        self.output.pull_progress(&mut produced[0]);
+
+        return false;   // capabilities announce any remaining work
+    }
+
+    fn notify_me(&self) -> bool { false }
+}
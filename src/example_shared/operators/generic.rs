@@ -0,0 +1,216 @@
+use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::default::Default;
+
+use progress::nested::subgraph::Source;
+use progress::nested::subgraph::Source::ScopeOutput;
+use progress::nested::subgraph::Target::ScopeInput;
+
+use communication::*;
+use progress::count_map::CountMap;
+use progress::notificator::Notificator;
+use progress::{Timestamp, Scope, Antichain};
+use communication::channels::ObserverHelper;
+
+use example_shared::*;
+use example_shared::operators::unary::PullableHelper;
+
+use drain::DrainExt;
+
+// Builds an operator with any number of inputs and outputs. Each input has its own contract, and
+// each output its own data type; both are handed back as handles, which the logic passed to build()
+// or build_notify() should capture and use directly.
+//
+// The operator's index is only allocated when it is built, so other operators may be added to the
+// graph in the meantime. Edges to its inputs are recorded and added once it is built, and the
+// streams of its outputs are available from their OperatorOutput once it is built.
+pub struct OperatorBuilder<G: GraphBuilder> {
+    name:       String,
+    index:      Rc<Cell<Option<u64>>>,  // set by build, and shared with each OperatorOutput
+    builder:    G,
+    edges:      Vec<(Source, u64)>,     // (source, input) edges to add once built
+    consumed:   Vec<Rc<RefCell<CountMap<G::Timestamp>>>>,
+    produced:   Vec<Rc<RefCell<CountMap<G::Timestamp>>>>,
+    summary:    Vec<Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>>,  // indexed [input][output]
+}
+
+impl<G: GraphBuilder> OperatorBuilder<G> {
+    pub fn new(name: String, builder: G) -> OperatorBuilder<G> {
+        OperatorBuilder {
+            name:       name,
+            index:      Rc::new(Cell::new(None)),
+            builder:    builder,
+            edges:      Vec::new(),
+            consumed:   Vec::new(),
+            produced:   Vec::new(),
+            summary:    Vec::new(),
+        }
+    }
+
+    // adds an input connected to stream. existing outputs are connected to it with the default summary.
+    pub fn new_input<D: Data, P: ParallelizationContract<G::Timestamp, D>>(&mut self, stream: &Stream<G, D>, pact: P)
+        -> PullableHelper<G::Timestamp, D, P::Pullable> {
+
+        let (sender, receiver) = pact.connect(&mut self.builder);
+        let consumed = Rc::new(RefCell::new(CountMap::new()));
+
+        stream.add_observer(sender);
+        self.edges.push((stream.name, self.consumed.len() as u64));
+
+        self.consumed.push(consumed.clone());
+        self.summary.push(vec![Antichain::from_elem(Default::default()); self.produced.len()]);
+
+        PullableHelper::new_shared(receiver, consumed)
+    }
+
+    // adds an output connected to each existing input with the default summary.
+    pub fn new_output<D: Data>(&mut self) -> (ObserverHelper<OutputPort<G::Timestamp, D>>, OperatorOutput<G, D>) {
+        let summaries = vec![Antichain::from_elem(Default::default()); self.consumed.len()];
+        self.new_output_with_summary(summaries)
+    }
+
+    // summaries[i] bounds the path from input i to this output; an empty antichain indicates that
+    // records received on input i never result in records sent on this output.
+    pub fn new_output_with_summary<D: Data>(&mut self, summaries: Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>)
+        -> (ObserverHelper<OutputPort<G::Timestamp, D>>, OperatorOutput<G, D>) {
+
+        assert!(summaries.len() == self.consumed.len());
+
+        let (targets, registrar) = OutputPort::<G::Timestamp, D>::new();
+        let produced = Rc::new(RefCell::new(CountMap::new()));
+        let output = self.produced.len() as u64;
+
+        for (input, summary) in summaries.into_iter().enumerate() {
+            self.summary[input].push(summary);
+        }
+        self.produced.push(produced.clone());
+
+        let stream = OperatorOutput {
+            index:      self.index.clone(),
+            output:     output,
+            registrar:  registrar,
+            builder:    self.builder.clone(),
+        };
+
+        (ObserverHelper::new(targets, produced), stream)
+    }
+
+    // builds an operator that neither receives progress information nor holds capabilities.
+    pub fn build<L: FnMut()+'static>(self, mut logic: L) {
+        self.build_scope(None, move |_| logic());
+    }
+
+    // builds an operator with a notificator, initially holding capabilities for each time in init.
+    // capabilities held by the notificator apply to all outputs.
+    pub fn build_notify<L: FnMut(&mut Notificator<G::Timestamp>)+'static>(self, init: Vec<G::Timestamp>, logic: L) {
+        let peers = self.builder.peers();
+        self.build_scope(Some((init, peers)), logic);
+    }
+
+    fn build_scope<L: FnMut(&mut Notificator<G::Timestamp>)+'static>(self, notify: Option<(Vec<G::Timestamp>, u64)>, logic: L) {
+        let scope = OperatorScope {
+            name:           self.name,
+            consumed:       self.consumed,
+            produced:       self.produced,
+            summary:        self.summary,
+            notificator:    Default::default(),
+            notify:         notify,
+            logic:          logic,
+        };
+
+        let index = self.builder.add_scope(scope);
+        for &(source, input) in self.edges.iter() {
+            self.builder.add_edge(source, ScopeInput(index, input));
+        }
+        self.index.set(Some(index));
+    }
+}
+
+// an output of an OperatorBuilder, whose stream is available once the operator is built.
+pub struct OperatorOutput<G: GraphBuilder, D: Data> {
+    index:      Rc<Cell<Option<u64>>>,
+    output:     u64,
+    registrar:  Registrar<G::Timestamp, D>,
+    builder:    G,
+}
+
+impl<G: GraphBuilder, D: Data> OperatorOutput<G, D> {
+    pub fn stream(self) -> Stream<G, D> {
+        match self.index.get() {
+            Some(index) => Stream::new(ScopeOutput(index, self.output), self.registrar, self.builder),
+            None        => panic!("OperatorOutput::stream(): operator not yet built"),
+        }
+    }
+}
+
+pub struct OperatorScope<T: Timestamp, L: FnMut(&mut Notificator<T>)> {
+    name:           String,
+    consumed:       Vec<Rc<RefCell<CountMap<T>>>>,
+    produced:       Vec<Rc<RefCell<CountMap<T>>>>,
+    summary:        Vec<Vec<Antichain<T::Summary>>>,
+    notificator:    Notificator<T>,
+    notify:         Option<(Vec<T>, u64)>,    // initial notifications and peers
+    logic:          L,
+}
+
+impl<T: Timestamp, L: FnMut(&mut Notificator<T>)> OperatorScope<T, L> {
+    // capabilities held by the notificator are reported on every output.
+    fn pull_notificator(&mut self, internal: &mut [CountMap<T>]) {
+        let mut changes = CountMap::new();
+        self.notificator.pull_progress(&mut changes);
+        for &(ref time, delta) in changes.elements().iter() {
+            for output in internal.iter_mut() {
+                output.update(time, delta);
+            }
+        }
+    }
+}
+
+impl<T: Timestamp, L: FnMut(&mut Notificator<T>)> Scope<T> for OperatorScope<T, L> {
+    fn inputs(&self) -> u64 { self.consumed.len() as u64 }
+    fn outputs(&self) -> u64 { self.produced.len() as u64 }
+
+    fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<T::Summary>>>, Vec<CountMap<T>>) {
+        let mut internal = vec![CountMap::new(); self.produced.len()];
+        if let Some((ref mut initial, peers)) = self.notify {
+            for time in initial.drain_temp() {
+                for _ in (0..peers) {
+                    self.notificator.notify_at(&time);
+                }
+            }
+        }
+
+        self.pull_notificator(&mut internal);
+        (self.summary.clone(), internal)
+    }
+
+    fn set_external_summary(&mut self, _summaries: Vec<Vec<Antichain<T::Summary>>>,
+                                       frontier: &mut [CountMap<T>]) -> () {
+        self.notificator.update_frontier_from_cm(frontier);
+    }
+
+    fn push_external_progress(&mut self, external: &mut [CountMap<T>]) -> () {
+        self.notificator.update_frontier_from_cm(external);
+    }
+
+    fn pull_internal_progress(&mut self, internal: &mut [CountMap<T>],
+                                         consumed: &mut [CountMap<T>],
+                                         produced: &mut [CountMap<T>]) -> bool
+    {
+        (self.logic)(&mut self.notificator);
+
+        for (index, counts) in self.consumed.iter().enumerate() {
+            counts.borrow_mut().drain_into(&mut consumed[index]);
+        }
+        for (index, counts) in self.produced.iter().enumerate() {
+            counts.borrow_mut().drain_into(&mut produced[index]);
+        }
+
+        self.pull_notificator(internal);
+
+        return false;   // no unannounced internal work
+    }
+
+    fn name(&self) -> String { format!("{}", self.name) }
+    fn notify_me(&self) -> bool { self.notify.is_some() }
+}
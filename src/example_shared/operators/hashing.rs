@@ -0,0 +1,8 @@
+use std::hash::{Hash, Hasher, SipHasher};
+
+// hashes a value with SipHasher, for use in routing records by key with Exchange.
+pub fn hash_code<T: Hash>(item: &T) -> u64 {
+    let mut hasher = SipHasher::new();
+    item.hash(&mut hasher);
+    hasher.finish()
+}
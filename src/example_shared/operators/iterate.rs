@@ -0,0 +1,49 @@
+use progress::nested::Summary::Local;
+
+use communication::*;
+use communication::pact::Pipeline;
+
+use example_shared::*;
+use example_shared::operators::feedback::{FeedbackExt, FeedbackConnectExt};
+use example_shared::operators::enterleave::{EnterSubgraphExt, LeaveSubgraphExt};
+use example_shared::operators::concat::ConcatExt;
+use example_shared::operators::unary::UnaryStreamExt;
+
+use drain::DrainExt;
+
+pub trait IterateExt<G: GraphBuilder, D: Data> {
+    // repeatedly applies logic to the stream and its results, in a new subgraph whose inner
+    // timestamp counts iterations. records produced by logic are fed back as the next iteration's
+    // input, unless they would exceed max_iterations, and are also returned from the subgraph.
+    // with no max_iterations, the loop runs to fixpoint: until a round produces no records.
+    fn iterate<F>(&self, max_iterations: Option<u64>, logic: F) -> Stream<G, D>
+    where F: FnOnce(&Stream<SubgraphBuilder<G, u64>, D>)->Stream<SubgraphBuilder<G, u64>, D>;
+}
+
+impl<G: GraphBuilder, D: Data> IterateExt<G, D> for Stream<G, D> {
+    fn iterate<F>(&self, max_iterations: Option<u64>, logic: F) -> Stream<G, D>
+    where F: FnOnce(&Stream<SubgraphBuilder<G, u64>, D>)->Stream<SubgraphBuilder<G, u64>, D> {
+
+        let mut builder = self.builder();
+        builder.subcomputation(|subgraph| {
+
+            let (handle, cycle) = subgraph.fixpoint_variable::<D>(Local(1));
+            let result = logic(&subgraph.enter(self).concat(&cycle));
+
+            // with max_iterations, records are fed back only while they remain within it.
+            if let Some(limit) = max_iterations {
+                result.unary_stream(Pipeline, format!("IterateLimit"), move |input, output| {
+                    while let Some((time, data)) = input.pull() {
+                        if time.inner + 1 < limit { output.give_at(&time, data.drain_temp()); }
+                    }
+                })
+                .connect_loop(handle);
+            }
+            else {
+                result.connect_loop(handle);
+            }
+
+            result.leave()
+        })
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use communication::*;
+use communication::pact::Exchange;
+
+use example_shared::*;
+use example_shared::operators::binary::BinaryNotifyExt;
+use example_shared::operators::hashing::hash_code;
+
+use columnar::Columnar;
+
+use drain::DrainExt;
+
+pub trait JoinExt<G: GraphBuilder, K: Data+Hash+Eq+Columnar, V1: Data+Columnar> {
+    // matches records with equal keys and equal times. state for a time is released once both
+    // inputs have passed it.
+    fn join<V2: Data+Columnar>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, V1, V2)>;
+
+    // matches each record of other against all records of self with equal key and time less or
+    // equal to it. records of self are retained indefinitely; records of other are joined once
+    // self has passed their time, and are then discarded.
+    fn join_incremental<V2: Data+Columnar>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, V1, V2)>;
+}
+
+impl<G: GraphBuilder, K: Data+Hash+Eq+Columnar, V1: Data+Columnar> JoinExt<G, K, V1> for Stream<G, (K, V1)>
+where G::Timestamp: Hash {
+
+    fn join<V2: Data+Columnar>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, V1, V2)> {
+        let mut state: HashMap<G::Timestamp, (HashMap<K, Vec<V1>>, HashMap<K, Vec<V2>>)> = HashMap::new();
+        let exch1 = Exchange::new(|x: &(K, V1)| hash_code(&x.0));
+        let exch2 = Exchange::new(|x: &(K, V2)| hash_code(&x.0));
+        self.binary_notify(other, exch1, exch2, format!("Join"), vec![], move |input1, input2, output, notificator| {
+            while let Some((time, data)) = input1.pull() {
+                if !state.contains_key(&time) {
+                    state.insert(time, (HashMap::new(), HashMap::new()));
+                    notificator.notify_at(&time);
+                }
+                let &mut (ref mut left, ref right) = state.get_mut(&time).unwrap();
+                let mut session = output.session(&time);
+                for (key, val1) in data.drain_temp() {
+                    if let Some(vals) = right.get(&key) {
+                        for val2 in vals.iter() {
+                            session.give((key.clone(), val1.clone(), val2.clone()));
+                        }
+                    }
+                    left.entry(key).or_insert(Vec::new()).push(val1);
+                }
+            }
+
+            while let Some((time, data)) = input2.pull() {
+                if !state.contains_key(&time) {
+                    state.insert(time, (HashMap::new(), HashMap::new()));
+                    notificator.notify_at(&time);
+                }
+                let &mut (ref left, ref mut right) = state.get_mut(&time).unwrap();
+                let mut session = output.session(&time);
+                for (key, val2) in data.drain_temp() {
+                    if let Some(vals) = left.get(&key) {
+                        for val1 in vals.iter() {
+                            session.give((key.clone(), val1.clone(), val2.clone()));
+                        }
+                    }
+                    right.entry(key).or_insert(Vec::new()).push(val2);
+                }
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                state.remove(&time);
+            }
+        })
+    }
+
+    fn join_incremental<V2: Data+Columnar>(&self, other: &Stream<G, (K, V2)>) -> Stream<G, (K, V1, V2)> {
+        let mut retained: HashMap<K, Vec<(G::Timestamp, V1)>> = HashMap::new();
+        let mut pending: HashMap<G::Timestamp, Vec<(K, V2)>> = HashMap::new();
+        let exch1 = Exchange::new(|x: &(K, V1)| hash_code(&x.0));
+        let exch2 = Exchange::new(|x: &(K, V2)| hash_code(&x.0));
+        self.binary_notify(other, exch1, exch2, format!("JoinIncremental"), vec![], move |input1, input2, output, notificator| {
+            while let Some((time, data)) = input1.pull() {
+                for (key, val1) in data.drain_temp() {
+                    retained.entry(key).or_insert(Vec::new()).push((time, val1));
+                }
+            }
+
+            // records of other wait until self can no longer produce records at times before them.
+            while let Some((time, data)) = input2.pull() {
+                if !pending.contains_key(&time) {
+                    pending.insert(time, Vec::new());
+                    notificator.notify_at_on(&time, &[0]);
+                }
+                pending.get_mut(&time).unwrap().extend(data.drain_temp());
+            }
+
+            while let Some((time, _count)) = notificator.next_on(&[0]) {
+                if let Some(mut data) = pending.remove(&time) {
+                    let mut session = output.session(&time);
+                    for (key, val2) in data.drain_temp() {
+                        if let Some(vals) = retained.get(&key) {
+                            for &(ref time1, ref val1) in vals.iter() {
+                                if time1 <= &time {
+                                    session.give((key.clone(), val1.clone(), val2.clone()));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
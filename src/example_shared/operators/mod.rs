@@ -1,6 +1,6 @@
 pub use self::enterleave::*;
 pub use self::unary::*;
-// pub use self::distinct::*;
+pub use self::distinct::*;
 pub use self::queue::*;
 pub use self::input::*;
 pub use self::feedback::*;
@@ -12,10 +12,26 @@ pub use self::flat_map::*;
 pub use self::filter::*;
 pub use self::binary::*;
 pub use self::delay::*;
+pub use self::generic::*;
+pub use self::branch::*;
+pub use self::hashing::*;
+pub use self::join::*;
+pub use self::aggregate::*;
+pub use self::iterate::*;
+pub use self::window::*;
+pub use self::sort::*;
+pub use self::top_k::*;
+pub use self::capture::*;
+pub use self::replay::*;
+pub use self::event_file::*;
+pub use self::file_source::*;
+pub use self::sink::*;
+pub use self::command::*;
+pub use self::event_time::*;
 
 pub mod enterleave;
 pub mod unary;
-// pub mod distinct;
+pub mod distinct;
 pub mod queue;
 pub mod input;
 pub mod feedback;
@@ -27,3 +43,19 @@ pub mod flat_map;
 pub mod filter;
 pub mod binary;
 pub mod delay;
+pub mod generic;
+pub mod branch;
+pub mod hashing;
+pub mod join;
+pub mod aggregate;
+pub mod iterate;
+pub mod window;
+pub mod sort;
+pub mod top_k;
+pub mod capture;
+pub mod replay;
+pub mod event_file;
+pub mod file_source;
+pub mod sink;
+pub mod command;
+pub mod event_time;
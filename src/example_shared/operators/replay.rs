@@ -0,0 +1,79 @@
+use std::default::Default;
+
+use progress::{Timestamp, Scope, Antichain};
+use progress::nested::subgraph::Source::ScopeOutput;
+use progress::count_map::CountMap;
+
+use communication::*;
+use communication::channels::ObserverHelper;
+
+use example_shared::*;
+use example_shared::operators::capture::Event;
+
+use drain::DrainExt;
+
+pub trait ReplayExt<G: GraphBuilder> {
+    // produces a stream from events captured by capture_into, with the captured frontier. each
+    // worker replays its own events; a worker with nothing to replay should still be given the
+    // event Progress(vec![(Default::default(), -1)]), or the stream will never complete.
+    fn replay<D: Data, P: Pullable<Event<G::Timestamp, D>>+'static>(&self, events: P) -> Stream<G, D>;
+}
+
+impl<G: GraphBuilder> ReplayExt<G> for G {
+    fn replay<D: Data, P: Pullable<Event<G::Timestamp, D>>+'static>(&self, events: P) -> Stream<G, D> {
+
+        let (targets, registrar) = OutputPort::<G::Timestamp, D>::new();
+        let produced = Default::default();
+
+        let index = self.add_scope(ReplayScope {
+            events: events,
+            output: ObserverHelper::new(targets, produced),
+            copies: self.peers(),
+        });
+
+        Stream::new(ScopeOutput(index, 0), registrar, self.clone())
+    }
+}
+
+// holds capabilities for the captured frontier, which starts from Default::default(). as with
+// other sources, the initial capability is reported once for each peer, and each worker's share
+// of it is released as its own captured frontier advances.
+pub struct ReplayScope<T: Timestamp, D: Data, P: Pullable<Event<T, D>>> {
+    events: P,
+    output: ObserverHelper<OutputPort<T, D>>,
+    copies: u64,
+}
+
+impl<T: Timestamp, D: Data, P: Pullable<Event<T, D>>> Scope<T> for ReplayScope<T, D, P> {
+    fn name(&self) -> String { format!("Replay") }
+    fn inputs(&self) -> u64 { 0 }
+    fn outputs(&self) -> u64 { 1 }
+
+    fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<T::Summary>>>, Vec<CountMap<T>>) {
+        (Vec::new(), vec![CountMap::new_from(&Default::default(), self.copies as i64)])
+    }
+
+    fn pull_internal_progress(&mut self, internal: &mut [CountMap<T>],
+                                        _consumed: &mut [CountMap<T>],
+                                         produced: &mut [CountMap<T>]) -> bool
+    {
+        while let Some(event) = self.events.pull() {
+            match event {
+                Event::Progress(changes) => {
+                    for &(ref time, delta) in changes.iter() {
+                        internal[0].update(time, delta);
+                    }
+                },
+                Event::Messages(time, mut data) => {
+                    self.output.give_at(&time, data.drain_temp());
+                },
+            }
+        }
+
+        self.output.pull_progress(&mut produced[0]);
+
+        return false;   // capabilities announce any remaining work
+    }
+
+    fn notify_me(&self) -> bool { false }
+}
@@ -0,0 +1,79 @@
+use std::fs::File;
+use std::io::{Write, BufWriter};
+use std::io::Result as IoResult;
+use std::hash::Hash;
+use std::collections::HashMap;
+
+use communication::*;
+use communication::pact::Pipeline;
+
+use example_shared::*;
+use example_shared::operators::unary::UnaryNotifyExt;
+
+use drain::DrainExt;
+
+pub trait WriteExt<G: GraphBuilder, D: Data> {
+    // writes each record as a line produced by format, once its time is complete. the lines of each
+    // time are written together, in the order in which times complete, after which the writer is
+    // flushed and the time produced on the returned stream. only times at which this worker received
+    // records are produced; the sink holds no capabilities for times it saw no records at.
+    fn write_to<W: Write+'static, F: Fn(&G::Timestamp, &D)->String+'static>(&self, writer: W, format: F) -> Stream<G, G::Timestamp>;
+
+    // as write_to, with each worker writing to the file at template with "{}" replaced by its index;
+    // template must contain "{}".
+    // the file is also synced to disk before each completed time is produced.
+    fn write_to_files<F: Fn(&G::Timestamp, &D)->String+'static>(&self, template: &str, format: F) -> Stream<G, G::Timestamp>;
+}
+
+impl<G: GraphBuilder, D: Data> WriteExt<G, D> for Stream<G, D> where G::Timestamp: Hash {
+    fn write_to<W: Write+'static, F: Fn(&G::Timestamp, &D)->String+'static>(&self, writer: W, format: F) -> Stream<G, G::Timestamp> {
+        write_with(self, format!("WriteTo"), writer, format, |writer: &mut W| writer.flush())
+    }
+
+    fn write_to_files<F: Fn(&G::Timestamp, &D)->String+'static>(&self, template: &str, format: F) -> Stream<G, G::Timestamp> {
+        assert!(template.contains("{}"), "write_to_files: template must contain \"{}\" for the worker index");
+        let path = template.replace("{}", &format!("{}", self.builder().index()));
+        let file = File::create(&path).ok().expect("write_to_files: could not create file");
+        write_with(self, format!("WriteToFiles"), BufWriter::new(file), format, |writer: &mut BufWriter<File>| {
+            try!(writer.flush());
+            writer.get_ref().sync_all()
+        })
+    }
+}
+
+// buffers formatted lines by time, and at each notification writes them, calls complete, and
+// produces the time. notifications are requested only for the times of received records, for which
+// the operator holds capabilities.
+fn write_with<G, D, W, F, C>(stream: &Stream<G, D>, name: String, mut writer: W, format: F, complete: C) -> Stream<G, G::Timestamp>
+where G: GraphBuilder,
+      G::Timestamp: Hash,
+      D: Data,
+      W: Write+'static,
+      F: Fn(&G::Timestamp, &D)->String+'static,
+      C: Fn(&mut W)->IoResult<()>+'static {
+
+    let mut lines: HashMap<G::Timestamp, Vec<String>> = HashMap::new();
+    stream.unary_notify(Pipeline, name, vec![], move |input, output, notificator| {
+        while let Some((time, data)) = input.pull() {
+            if !lines.contains_key(&time) {
+                lines.insert(time, Vec::new());
+                notificator.notify_at(&time);
+            }
+            let lines = lines.get_mut(&time).unwrap();
+            for datum in data.drain_temp() {
+                lines.push(format(&time, &datum));
+            }
+        }
+
+        while let Some((time, _count)) = notificator.next() {
+            if let Some(lines) = lines.remove(&time) {
+                for line in lines.into_iter() {
+                    writer.write_all(line.as_bytes()).ok().expect("write: write error");
+                    writer.write_all(b"\n").ok().expect("write: write error");
+                }
+                complete(&mut writer).ok().expect("write: flush error");
+                output.give_at(&time, Some(time).into_iter());
+            }
+        }
+    })
+}
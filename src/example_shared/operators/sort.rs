@@ -0,0 +1,160 @@
+use std::rc::Rc;
+use std::cmp;
+use std::hash::Hash;
+use std::collections::HashMap;
+
+use communication::*;
+use communication::pact::{Pipeline, Partition};
+
+use example_shared::*;
+use example_shared::operators::unary::UnaryNotifyExt;
+use example_shared::operators::binary::BinaryNotifyExt;
+
+use columnar::Columnar;
+
+use drain::DrainExt;
+
+pub trait SortExt<G: GraphBuilder, D: Data> {
+    // sorts the records each worker holds for each time, producing them once the time is complete.
+    fn sort_local<K: Ord, F: Fn(&D)->K+'static>(&self, key: F) -> Self;
+
+    // sorts the records of each time across workers: worker i receives the i-th range of keys, and
+    // produces it in sorted order once the time is complete. ranges are determined by sampling the
+    // keys of each time, and are only as balanced as the sample is representative.
+    fn sort_global<K: Ord+Data+Columnar, F: Fn(&D)->K+'static>(&self, key: F) -> Self where D: Columnar;
+}
+
+impl<G: GraphBuilder, D: Data> SortExt<G, D> for Stream<G, D> where G::Timestamp: Hash {
+
+    fn sort_local<K: Ord, F: Fn(&D)->K+'static>(&self, key: F) -> Stream<G, D> {
+        let mut buffers: HashMap<G::Timestamp, Vec<D>> = HashMap::new();
+        self.unary_notify(Pipeline, format!("SortLocal"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                if !buffers.contains_key(&time) {
+                    buffers.insert(time, Vec::new());
+                    notificator.notify_at(&time);
+                }
+                buffers.get_mut(&time).unwrap().extend(data.drain_temp());
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some(mut data) = buffers.remove(&time) {
+                    data.sort_by(|x, y| key(x).cmp(&key(y)));
+                    output.give_at(&time, data.drain_temp());
+                }
+            }
+        })
+    }
+
+    fn sort_global<K: Ord+Data+Columnar, F: Fn(&D)->K+'static>(&self, key: F) -> Stream<G, D> where D: Columnar {
+
+        let peers = self.builder().peers();
+        let oversample = 16;        // samples per peer contributed by each worker, for each time
+        let key = Rc::new(key);
+
+        // each worker samples its keys for each time, and sends the sample to every worker.
+        let sample_key = key.clone();
+        let mut keys: HashMap<G::Timestamp, Vec<K>> = HashMap::new();
+        let samples = self.unary_notify(Pipeline, format!("SortSample"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                if !keys.contains_key(&time) {
+                    keys.insert(time, Vec::new());
+                    notificator.notify_at(&time);
+                }
+                let keys = keys.get_mut(&time).unwrap();
+                for datum in data.iter() { keys.push((*sample_key)(datum)); }
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some(mut keys) = keys.remove(&time) {
+                    keys.sort();
+                    let count = cmp::min(keys.len() as u64, peers * oversample);
+                    let mut session = output.session(&time);
+                    for index in (0..count) {
+                        let sample = keys[(index * keys.len() as u64 / count) as usize].clone();
+                        for worker in (0..peers) {
+                            session.give((worker, sample.clone()));
+                        }
+                    }
+                }
+            }
+        });
+
+        // every worker sees every sample, and derives the same range boundaries from them.
+        let mut gathered: HashMap<G::Timestamp, Vec<K>> = HashMap::new();
+        let splitters = samples.unary_notify(Partition::new(|x: &(u64, K)| x.0), format!("SortSplitters"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                if !gathered.contains_key(&time) {
+                    gathered.insert(time, Vec::new());
+                    notificator.notify_at(&time);
+                }
+                gathered.get_mut(&time).unwrap().extend(data.drain_temp().map(|(_, sample)| sample));
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some(mut samples) = gathered.remove(&time) {
+                    samples.sort();
+                    let mut splitters = Vec::new();
+                    if samples.len() > 0 {
+                        for worker in (1..peers) {
+                            splitters.push(samples[(worker * samples.len() as u64 / peers) as usize].clone());
+                        }
+                    }
+                    output.give_at(&time, Some(splitters).into_iter());
+                }
+            }
+        });
+
+        // records are held until the boundaries for their time are known, then tagged with the
+        // index of their range and sent to the worker with that index.
+        let route_key = key.clone();
+        let mut pending: HashMap<G::Timestamp, (Vec<D>, Vec<K>)> = HashMap::new();
+        let routed = self.binary_notify(&splitters, Pipeline, Pipeline, format!("SortRoute"), vec![], move |input1, input2, output, notificator| {
+            while let Some((time, data)) = input1.pull() {
+                if !pending.contains_key(&time) {
+                    pending.insert(time, (Vec::new(), Vec::new()));
+                    notificator.notify_at(&time);
+                }
+                pending.get_mut(&time).unwrap().0.extend(data.drain_temp());
+            }
+            while let Some((time, data)) = input2.pull() {
+                if !pending.contains_key(&time) {
+                    pending.insert(time, (Vec::new(), Vec::new()));
+                    notificator.notify_at(&time);
+                }
+                for splitters in data.drain_temp() {
+                    pending.get_mut(&time).unwrap().1 = splitters;
+                }
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some((mut data, splitters)) = pending.remove(&time) {
+                    let mut session = output.session(&time);
+                    for datum in data.drain_temp() {
+                        let key = (*route_key)(&datum);
+                        let range = splitters.iter().take_while(|x| *x <= &key).count() as u64;
+                        session.give((range, datum));
+                    }
+                }
+            }
+        });
+
+        let mut buffers: HashMap<G::Timestamp, Vec<D>> = HashMap::new();
+        routed.unary_notify(Partition::new(|x: &(u64, D)| x.0), format!("SortGlobal"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                if !buffers.contains_key(&time) {
+                    buffers.insert(time, Vec::new());
+                    notificator.notify_at(&time);
+                }
+                buffers.get_mut(&time).unwrap().extend(data.drain_temp().map(|(_, datum)| datum));
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some(mut data) = buffers.remove(&time) {
+                    data.sort_by(|x, y| (*key)(x).cmp(&(*key)(y)));
+                    output.give_at(&time, data.drain_temp());
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,162 @@
+use std::rc::Rc;
+use std::hash::Hash;
+use std::cmp::Ordering;
+use std::collections::{HashMap, BinaryHeap};
+
+use communication::*;
+use communication::pact::{Pipeline, Exchange};
+
+use example_shared::*;
+use example_shared::operators::unary::UnaryNotifyExt;
+use example_shared::operators::hashing::hash_code;
+
+use columnar::Columnar;
+
+use drain::DrainExt;
+
+pub trait TopKExt<G: GraphBuilder, D: Data+Columnar> {
+    // produces the k records of each time with the largest rank, in decreasing order of rank. each
+    // worker first selects its own k records, and these are then combined at worker zero.
+    fn top_k<R: Ord+'static, F: Fn(&D)->R+'static>(&self, k: usize, rank: F) -> Self;
+}
+
+pub trait TopKByKeyExt<G: GraphBuilder, K: Data+Hash+Eq+Columnar, V: Data+Columnar> {
+    // produces the k records of each key and time with the largest rank, in decreasing order of rank.
+    // each worker first selects its own k records per key, and these are then exchanged by key.
+    fn top_k_by_key<R: Ord+'static, F: Fn(&V)->R+'static>(&self, k: usize, rank: F) -> Self;
+}
+
+impl<G: GraphBuilder, D: Data+Columnar> TopKExt<G, D> for Stream<G, D> where G::Timestamp: Hash {
+    fn top_k<R: Ord+'static, F: Fn(&D)->R+'static>(&self, k: usize, rank: F) -> Stream<G, D> {
+        let rank = Rc::new(rank);
+        let local_rank = rank.clone();
+        let mut local: HashMap<G::Timestamp, BinaryHeap<Ranked<R, D>>> = HashMap::new();
+        let mut global: HashMap<G::Timestamp, BinaryHeap<Ranked<R, D>>> = HashMap::new();
+        self.unary_notify(Pipeline, format!("TopKLocal"), vec![], move |input, output, notificator| {
+                while let Some((time, data)) = input.pull() {
+                    if !local.contains_key(&time) {
+                        local.insert(time, BinaryHeap::new());
+                        notificator.notify_at(&time);
+                    }
+                    let heap = local.get_mut(&time).unwrap();
+                    for datum in data.drain_temp() {
+                        offer(heap, k, (*local_rank)(&datum), datum);
+                    }
+                }
+
+                while let Some((time, _count)) = notificator.next() {
+                    if let Some(heap) = local.remove(&time) {
+                        output.give_at(&time, heap.into_iter().map(|x| x.datum));
+                    }
+                }
+            })
+            .unary_notify(Exchange::new(|_: &D| 0), format!("TopK"), vec![], move |input, output, notificator| {
+                while let Some((time, data)) = input.pull() {
+                    if !global.contains_key(&time) {
+                        global.insert(time, BinaryHeap::new());
+                        notificator.notify_at(&time);
+                    }
+                    let heap = global.get_mut(&time).unwrap();
+                    for datum in data.drain_temp() {
+                        offer(heap, k, (*rank)(&datum), datum);
+                    }
+                }
+
+                while let Some((time, _count)) = notificator.next() {
+                    if let Some(heap) = global.remove(&time) {
+                        output.give_at(&time, heap.into_sorted_vec().into_iter().map(|x| x.datum));
+                    }
+                }
+            })
+    }
+}
+
+impl<G: GraphBuilder, K: Data+Hash+Eq+Columnar, V: Data+Columnar> TopKByKeyExt<G, K, V> for Stream<G, (K, V)>
+where G::Timestamp: Hash {
+    fn top_k_by_key<R: Ord+'static, F: Fn(&V)->R+'static>(&self, k: usize, rank: F) -> Stream<G, (K, V)> {
+        let rank = Rc::new(rank);
+        let local_rank = rank.clone();
+        let mut local: HashMap<G::Timestamp, HashMap<K, BinaryHeap<Ranked<R, V>>>> = HashMap::new();
+        let mut global: HashMap<G::Timestamp, HashMap<K, BinaryHeap<Ranked<R, V>>>> = HashMap::new();
+        self.unary_notify(Pipeline, format!("TopKByKeyLocal"), vec![], move |input, output, notificator| {
+                while let Some((time, data)) = input.pull() {
+                    if !local.contains_key(&time) {
+                        local.insert(time, HashMap::new());
+                        notificator.notify_at(&time);
+                    }
+                    let heaps = local.get_mut(&time).unwrap();
+                    for (key, val) in data.drain_temp() {
+                        let ranking = (*local_rank)(&val);
+                        offer(heaps.entry(key).or_insert(BinaryHeap::new()), k, ranking, val);
+                    }
+                }
+
+                while let Some((time, _count)) = notificator.next() {
+                    if let Some(heaps) = local.remove(&time) {
+                        let mut session = output.session(&time);
+                        for (key, heap) in heaps.into_iter() {
+                            for ranked in heap.into_iter() {
+                                session.give((key.clone(), ranked.datum));
+                            }
+                        }
+                    }
+                }
+            })
+            .unary_notify(Exchange::new(|x: &(K, V)| hash_code(&x.0)), format!("TopKByKey"), vec![], move |input, output, notificator| {
+                while let Some((time, data)) = input.pull() {
+                    if !global.contains_key(&time) {
+                        global.insert(time, HashMap::new());
+                        notificator.notify_at(&time);
+                    }
+                    let heaps = global.get_mut(&time).unwrap();
+                    for (key, val) in data.drain_temp() {
+                        let ranking = (*rank)(&val);
+                        offer(heaps.entry(key).or_insert(BinaryHeap::new()), k, ranking, val);
+                    }
+                }
+
+                while let Some((time, _count)) = notificator.next() {
+                    if let Some(heaps) = global.remove(&time) {
+                        let mut session = output.session(&time);
+                        for (key, heap) in heaps.into_iter() {
+                            for ranked in heap.into_sorted_vec().into_iter() {
+                                session.give((key.clone(), ranked.datum));
+                            }
+                        }
+                    }
+                }
+            })
+    }
+}
+
+// a record and its rank, ordered so that a BinaryHeap of them has the smallest rank at its top.
+struct Ranked<R: Ord, D> {
+    rank:   R,
+    datum:  D,
+}
+
+impl<R: Ord, D> PartialEq for Ranked<R, D> { fn eq(&self, other: &Ranked<R, D>) -> bool { self.rank == other.rank } }
+impl<R: Ord, D> Eq for Ranked<R, D> { }
+impl<R: Ord, D> PartialOrd for Ranked<R, D> {
+    fn partial_cmp(&self, other: &Ranked<R, D>) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+impl<R: Ord, D> Ord for Ranked<R, D> {
+    fn cmp(&self, other: &Ranked<R, D>) -> Ordering { other.rank.cmp(&self.rank) }
+}
+
+// retains the datum if the heap holds fewer than k records, or if it outranks the smallest of them.
+fn offer<R: Ord, D>(heap: &mut BinaryHeap<Ranked<R, D>>, k: usize, rank: R, datum: D) {
+    if heap.len() < k {
+        heap.push(Ranked { rank: rank, datum: datum });
+    }
+    else {
+        let replace = match heap.peek() {
+            Some(smallest) => rank > smallest.rank,
+            None           => false,
+        };
+        if replace {
+            heap.pop();
+            heap.push(Ranked { rank: rank, datum: datum });
+        }
+    }
+}
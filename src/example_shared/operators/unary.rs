@@ -19,7 +19,7 @@ use drain::DrainExt;
 
 pub struct PullableHelper<T:Timestamp, D: Data, P: Pullable<(T, Vec<D>)>> {
     receiver:   PactPullable<T, D, P>,
-    consumed:   CountMap<T>,
+    consumed:   Rc<RefCell<CountMap<T>>>,
     phantom:    PhantomData<D>,
 }
 
@@ -27,7 +27,7 @@ impl<T:Timestamp, D: Data, P: Pullable<(T, Vec<D>)>> PullableHelper<T, D, P> {
     pub fn pull(&mut self) -> Option<(T, &mut Vec<D>)> {
         if let Some((time, data)) = self.receiver.pull() {
             if data.len() > 0 {
-                self.consumed.update(&time, data.len() as i64);
+                self.consumed.borrow_mut().update(&time, data.len() as i64);
                 Some((time, data))
             }
             else { None }
@@ -38,14 +38,18 @@ impl<T:Timestamp, D: Data, P: Pullable<(T, Vec<D>)>> PullableHelper<T, D, P> {
 
 impl<T:Timestamp, D:Data, P: Pullable<(T, Vec<D>)>> PullableHelper<T, D, P> {
     pub fn new(input: PactPullable<T, D, P>) -> PullableHelper<T, D, P> {
+        PullableHelper::new_shared(input, Rc::new(RefCell::new(CountMap::new())))
+    }
+    // consumption counts are shared, so that they can be read without access to the helper.
+    pub fn new_shared(input: PactPullable<T, D, P>, consumed: Rc<RefCell<CountMap<T>>>) -> PullableHelper<T, D, P> {
         PullableHelper {
             receiver: input,
-            consumed: CountMap::new(),
+            consumed: consumed,
             phantom:  PhantomData,
         }
     }
     pub fn pull_progress(&mut self, consumed: &mut CountMap<T>) {
-        while let Some((ref time, value)) = self.consumed.pop() {
+        while let Some((ref time, value)) = self.consumed.borrow_mut().pop() {
             consumed.update(time, value);
         }
     }
@@ -110,6 +114,38 @@ impl<G: GraphBuilder, D1: Data> UnaryStreamExt<G, D1> for Stream<G, D1> {
     }
 }
 
+// like unary_stream, but the logic may also inspect the frontier of its input. the operator
+// receives progress updates, but holds no capabilities and cannot request notifications.
+pub trait UnaryFrontierExt<G: GraphBuilder, D1: Data> {
+    fn unary_frontier<D2: Data,
+             L: FnMut(&mut PullableHelper<G::Timestamp, D1, P::Pullable>,
+                      &mut ObserverHelper<OutputPort<G::Timestamp, D2>>,
+                      &[G::Timestamp])+'static,
+             P: ParallelizationContract<G::Timestamp, D1>>
+            (&self, pact: P, name: String, logic: L) -> Stream<G, D2>;
+}
+
+impl<G: GraphBuilder, D1: Data> UnaryFrontierExt<G, D1> for Stream<G, D1> {
+    fn unary_frontier<D2: Data,
+             L: FnMut(&mut PullableHelper<G::Timestamp, D1, P::Pullable>,
+                      &mut ObserverHelper<OutputPort<G::Timestamp, D2>>,
+                      &[G::Timestamp])+'static,
+             P: ParallelizationContract<G::Timestamp, D1>>
+             (&self, pact: P, name: String, mut logic: L) -> Stream<G, D2> {
+
+        let mut builder = self.builder();
+
+        let (sender, receiver) = pact.connect(&mut builder);
+        let (targets, registrar) = OutputPort::<G::Timestamp,D2>::new();
+        let notify = Some((vec![], builder.peers()));   // interested in progress, but no capabilities
+        let scope = UnaryScope::new(receiver, targets, name, move |i,o,n| logic(i,o,n.frontier(0)), notify);
+        let index = builder.add_scope(scope);
+        self.connect_to(ScopeInput(index, 0), sender);
+
+        Stream::new(ScopeOutput(index, 0), registrar, builder)
+    }
+}
+
 pub struct UnaryScopeHandle<T: Timestamp, D1: Data, D2: Data, P: Pullable<(T, Vec<D1>)>> {
     pub input:          PullableHelper<T, D1, P>,
     pub output:         ObserverHelper<OutputPort<T, D2>>,
@@ -0,0 +1,107 @@
+use std::hash::Hash;
+use std::collections::HashMap;
+
+use communication::*;
+use communication::pact::Pipeline;
+
+use example_shared::*;
+use example_shared::operators::unary::UnaryNotifyExt;
+use example_shared::operators::event_time::EventTime;
+
+use drain::DrainExt;
+
+// Windows group records by the event time of their timestamps, and apply aggregate to the records
+// of each window once the input frontier has passed the window's end. aggregate is called with the
+// window's [start, end) range, and its result is produced at the last time of the window. Windows
+// are formed independently by each worker; exchange the stream first to combine across workers.
+pub trait WindowExt<G: GraphBuilder, D: Data> where G::Timestamp: EventTime+Hash {
+    fn tumbling_window<R: Data, F: Fn(u64, u64, Vec<D>)->R+'static>(&self, width: u64, aggregate: F) -> Stream<G, R>;
+    fn sliding_window<R: Data, F: Fn(u64, u64, Vec<D>)->R+'static>(&self, width: u64, slide: u64, aggregate: F) -> Stream<G, R>;
+
+    // a session is a maximal set of records whose successive event times are less than gap apart.
+    fn session_window<R: Data, F: Fn(u64, u64, Vec<D>)->R+'static>(&self, gap: u64, aggregate: F) -> Stream<G, R>;
+}
+
+impl<G: GraphBuilder, D: Data> WindowExt<G, D> for Stream<G, D> where G::Timestamp: EventTime+Hash {
+
+    fn tumbling_window<R: Data, F: Fn(u64, u64, Vec<D>)->R+'static>(&self, width: u64, aggregate: F) -> Stream<G, R> {
+        assert!(width > 0);
+        self.sliding_window(width, width, aggregate)
+    }
+
+    fn sliding_window<R: Data, F: Fn(u64, u64, Vec<D>)->R+'static>(&self, width: u64, slide: u64, aggregate: F) -> Stream<G, R> {
+        assert!(width > 0 && slide > 0);
+        let mut windows: HashMap<G::Timestamp, Vec<D>> = HashMap::new();
+        self.unary_notify(Pipeline, format!("SlidingWindow"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                // windows [start, start + width) with start a multiple of slide, containing the event time.
+                let event = time.event_time();
+                let lower = if event + 1 >= width { event + 1 - width } else { 0 };
+                let mut start = ((lower + slide - 1) / slide) * slide;
+                while start <= event {
+                    let last = time.with_event_time(start + width - 1);
+                    if !windows.contains_key(&last) {
+                        notificator.notify_at(&last);
+                        windows.insert(last, Vec::new());
+                    }
+                    windows.get_mut(&last).unwrap().extend(data.iter().cloned());
+                    start += slide;
+                }
+                data.clear();
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                if let Some(records) = windows.remove(&time) {
+                    let end = time.event_time() + 1;
+                    output.give_at(&time, Some(aggregate(end - width, end, records)).into_iter());
+                }
+            }
+        })
+    }
+
+    fn session_window<R: Data, F: Fn(u64, u64, Vec<D>)->R+'static>(&self, gap: u64, aggregate: F) -> Stream<G, R> {
+        assert!(gap > 0);
+        // open sessions (first, last event time, records), for each timestamp with event time zeroed.
+        let mut sessions: HashMap<G::Timestamp, Vec<(u64, u64, Vec<D>)>> = HashMap::new();
+        self.unary_notify(Pipeline, format!("SessionWindow"), vec![], move |input, output, notificator| {
+            while let Some((time, data)) = input.pull() {
+                let event = time.event_time();
+                let open = sessions.entry(time.with_event_time(0)).or_insert(Vec::new());
+
+                // merge all sessions this event time falls within gap of.
+                let mut session = (event, event, data.drain_temp().collect::<Vec<_>>());
+                let mut index = 0;
+                while index < open.len() {
+                    if open[index].0 < event + gap && event < open[index].1 + gap {
+                        let (first, last, mut records) = open.swap_remove(index);
+                        if first < session.0 { session.0 = first; }
+                        if last > session.1 { session.1 = last; }
+                        session.2.extend(records.drain_temp());
+                    }
+                    else { index += 1; }
+                }
+
+                // the session closes once no further event time can fall within gap of its last.
+                notificator.notify_at(&time.with_event_time(session.1 + gap - 1));
+                open.push(session);
+            }
+
+            while let Some((time, _count)) = notificator.next() {
+                let key = time.with_event_time(0);
+                let mut empty = false;
+                if let Some(open) = sessions.get_mut(&key) {
+                    let mut index = 0;
+                    while index < open.len() {
+                        if open[index].1 + gap - 1 <= time.event_time() {
+                            let (first, last, records) = open.swap_remove(index);
+                            output.give_at(&time, Some(aggregate(first, last + 1, records)).into_iter());
+                        }
+                        else { index += 1; }
+                    }
+                    empty = open.len() == 0;
+                }
+                if empty { sessions.remove(&key); }
+            }
+        })
+    }
+}
@@ -22,6 +22,12 @@ impl<G: GraphBuilder, D:Data> Stream<G, D> {
         self.ports.add_observer(observer);
     }
 
+    // registers observer without adding an edge, which the caller must add to the graph itself.
+    pub fn add_observer<O>(&self, observer: O)
+    where O: Observer<Time=G::Timestamp, Data=D>+'static {
+        self.ports.add_observer(observer);
+    }
+
     pub fn new(source: Source, output: Registrar<G::Timestamp, D>, builder: G) -> Self {
         Stream { name: source, ports: output, builder: builder }
     }
@@ -237,7 +237,9 @@ impl<W: Write> BinarySender<W> {
     }
 }
 
-pub fn initialize_networking(addresses: Vec<String>, my_index: u64, workers: u64) -> Result<Vec<BinaryCommunicator>> {
+// progress_arity, if set, is the number of children of each process in the tree through which
+// progress updates are aggregated; otherwise each process broadcasts its updates to all others.
+pub fn initialize_networking(addresses: Vec<String>, my_index: u64, workers: u64, progress_arity: Option<u64>) -> Result<Vec<BinaryCommunicator>> {
 
     let processes = addresses.len() as u64;
     let hosts1 = Arc::new(addresses);
@@ -287,16 +289,13 @@ pub fn initialize_networking(addresses: Vec<String>, my_index: u64, workers: u64
 
     let mut results = Vec::new();
     for (index, proc_comm) in proc_comms.into_iter().enumerate() {
-        results.push(BinaryCommunicator {
-            inner:          proc_comm,
-            index:          my_index * workers + index as u64,
-            peers:          workers * processes,
-            graph:          0,          // TODO : Fix this
-            allocated:      0,
-            writers:        writers.clone(),
-            readers:        readers.clone(),
-            senders:        senders.clone(),
-        });
+        results.push(BinaryCommunicator::new(proc_comm,
+                                             my_index * workers + index as u64,
+                                             workers * processes,
+                                             progress_arity,
+                                             writers.clone(),
+                                             readers.clone(),
+                                             senders.clone()));
     }
 
     return Ok(results);
@@ -1,4 +1,7 @@
+use std::mem;
+
 use progress::Timestamp;
+use progress::count_map::CountMap;
 use communication::{Communicator, Pushable, Pullable};
 use columnar::Columnar;
 
@@ -6,33 +9,148 @@ use drain::DrainExt;
 
 pub type ProgressVec<T> = Vec<(u64, u64, T, i64)>;  // (child_scope, [in/out]port, timestamp, delta)
 
+type ProgressBatch<T> = (ProgressVec<T>, ProgressVec<T>);
+
+// Workers are grouped local_peers() at a time, and the first worker of each group is its leader.
+// Other workers send their updates to their leader, which combines them with its own (and those of
+// any child leaders) into one consolidated batch. Leaders either broadcast their batches to all
+// leaders, or, given a progress_arity(), pass them up a tree of leaders with that many children
+// each, whose root sends them back down. Each batch is then passed down to the leader's workers.
+//
+// With a single process and no arity every worker is its own leader, and updates are broadcast to
+// all workers directly. Batches from one worker are never reordered, and are applied atomically.
+// The `progress` example in main_shared checks that grouped workers with a tree of leaders see the
+// same notifications as broadcast progress, simulating processes within one.
 pub struct Progcaster<T:Timestamp> {
-    senders:    Vec<Box<Pushable<(ProgressVec<T>, ProgressVec<T>)>>>,
-    receiver:   Box<Pullable<(ProgressVec<T>, ProgressVec<T>)>>,
+    peers:      u64,
+    parent:     Option<Box<Pushable<ProgressBatch<T>>>>,    // where our batches go, if not to the leaders
+    leaders:    Vec<Box<Pushable<ProgressBatch<T>>>>,       // every leader, if we broadcast to them
+    children:   Vec<Box<Pushable<ProgressBatch<T>>>>,       // workers to pass batches down to
+    from_below: Option<Box<Pullable<ProgressBatch<T>>>>,    // batches from children, to combine with ours
+    from_above: Option<Box<Pullable<ProgressBatch<T>>>>,    // batches to apply and pass down
 }
 
 impl<T:Timestamp+Send+Columnar> Progcaster<T> {
     pub fn new<C: Communicator>(communicator: &mut C) -> Progcaster<T> {
-        let (senders, receiver) = communicator.new_channel();
-        Progcaster { senders: senders, receiver: receiver }
+
+        let index = communicator.index();
+        let peers = communicator.peers();
+        let arity = communicator.progress_arity();
+
+        // with one process, grouping workers would only delay their updates.
+        let group = if communicator.local_peers() < peers || arity.is_some() { communicator.local_peers() } else { 1 };
+        let group = if group > 0 { group } else { 1 };
+
+        let leader = index - index % group;
+        let rank = index / group;                   // position of our group among groups
+        let groups = (peers + group - 1) / group;
+
+        // every worker allocates the same channels, whether or not it uses them.
+        let relayed = group > 1 || arity.is_some();
+        let (up_senders, up_receiver) = if relayed { let (s, r) = communicator.new_channel(); (s, Some(r)) } else { (Vec::new(), None) };
+        let (down_senders, down_receiver) = if relayed { let (s, r) = communicator.new_channel(); (s, Some(r)) } else { (Vec::new(), None) };
+        let (leader_senders, leader_receiver) = if arity.is_none() { let (s, r) = communicator.new_channel(); (s, Some(r)) } else { (Vec::new(), None) };
+
+        let mut up_senders: Vec<Option<_>> = up_senders.into_iter().map(Some).collect();
+        let mut down_senders: Vec<Option<_>> = down_senders.into_iter().map(Some).collect();
+
+        let mut result = Progcaster {
+            peers:      peers,
+            parent:     None,
+            leaders:    Vec::new(),
+            children:   Vec::new(),
+            from_below: None,
+            from_above: None,
+        };
+
+        if index != leader {
+            result.parent = up_senders[leader as usize].take();
+            result.from_above = down_receiver;
+        }
+        else {
+            // our group's other workers are children, as are our child leaders in a tree.
+            for worker in ((index + 1)..::std::cmp::min(index + group, peers)) {
+                result.children.push(down_senders[worker as usize].take().unwrap());
+            }
+            result.from_below = up_receiver;
+
+            match arity {
+                Some(arity) => {
+                    for child in ((rank * arity + 1)..::std::cmp::min(rank * arity + arity + 1, groups)) {
+                        result.children.push(down_senders[(child * group) as usize].take().unwrap());
+                    }
+                    if rank > 0 {
+                        result.parent = up_senders[(((rank - 1) / arity) * group) as usize].take();
+                        result.from_above = down_receiver;
+                    }
+                },
+                None => {
+                    let mut leader_senders: Vec<Option<_>> = leader_senders.into_iter().map(Some).collect();
+                    for group_rank in (0..groups) {
+                        result.leaders.push(leader_senders[(group_rank * group) as usize].take().unwrap());
+                    }
+                    result.from_above = leader_receiver;
+                },
+            }
+        }
+
+        result
     }
+
     pub fn send_and_recv(&mut self, messages: &mut ProgressVec<T>, internal: &mut ProgressVec<T>) -> () {
-        if self.senders.len() > 1 {  // if the length is one, just return the updates...
-            if messages.len() > 0 || internal.len() > 0 {
-                for sender in self.senders.iter_mut() {
-                    sender.push((messages.clone(), internal.clone()));
+        if self.peers > 1 {  // if there is one peer, just return the updates...
+
+            // our updates travel with any from our children, as one consolidated batch.
+            let mut batch = (mem::replace(messages, Vec::new()), mem::replace(internal, Vec::new()));
+            if let Some(ref mut from_below) = self.from_below {
+                while let Some((mut recv_messages, mut recv_internal)) = from_below.pull() {
+                    batch.0.extend(recv_messages.drain_temp());
+                    batch.1.extend(recv_internal.drain_temp());
                 }
+                let mut counts = CountMap::new();
+                consolidate(&mut batch.0, &mut counts);
+                consolidate(&mut batch.1, &mut counts);
+            }
 
-                messages.clear();
-                internal.clear();
+            if batch.0.len() > 0 || batch.1.len() > 0 {
+                if let Some(ref mut parent) = self.parent {
+                    parent.push(batch);
+                }
+                else if self.leaders.len() > 0 {
+                    for leader in self.leaders.iter_mut() {
+                        leader.push(batch.clone());
+                    }
+                }
+                else {
+                    // the root of a tree of leaders sends its batches straight back down.
+                    deliver(batch, &mut self.children, messages, internal);
+                }
             }
 
-            while let Some((mut recv_messages, mut recv_internal)) = self.receiver.pull() {
-                // messages.append(&mut recv_messages);
-                // internal.append(&mut recv_internal);
-                messages.extend(recv_messages.drain_temp());
-                internal.extend(recv_internal.drain_temp());
+            if let Some(ref mut from_above) = self.from_above {
+                while let Some(batch) = from_above.pull() {
+                    deliver(batch, &mut self.children, messages, internal);
+                }
             }
         }
     }
 }
+
+// passes a batch to each child, and adds it to the updates to apply.
+fn deliver<T: Timestamp>(mut batch: ProgressBatch<T>,
+                         children: &mut Vec<Box<Pushable<ProgressBatch<T>>>>,
+                         messages: &mut ProgressVec<T>,
+                         internal: &mut ProgressVec<T>) {
+    for child in children.iter_mut() {
+        child.push(batch.clone());
+    }
+    messages.extend(batch.0.drain_temp());
+    internal.extend(batch.1.drain_temp());
+}
+
+// accumulates updates to the same (scope, port, time) using counts, which is left empty, discarding
+// those that cancel.
+pub fn consolidate<T: Timestamp>(updates: &mut ProgressVec<T>, counts: &mut CountMap<(u64, u64, T)>) {
+    while let Some((scope, port, time, delta)) = updates.pop() { counts.update(&(scope, port, time), delta); }
+    while let Some(((scope, port, time), delta)) = counts.pop() { updates.push((scope, port, time, delta)); }
+}
@@ -11,3 +11,4 @@ pub mod timestamp;
 pub mod scope;
 pub mod broadcast;
 pub mod notificator;
+pub mod remote;
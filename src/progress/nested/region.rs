@@ -0,0 +1,128 @@
+use std::default::Default;
+
+use progress::{Timestamp, Scope, CountMap, Antichain};
+use progress::timestamp::{RootTimestamp, RootSummary};
+use progress::nested::product::Product;
+use progress::nested::summary::Summary;
+use progress::nested::summary::Summary::{Local, Outer};
+
+// Presents a Scope<T> as a Scope<Product<T, RootTimestamp>>, so that it can be a child of a
+// Subgraph<T, RootTimestamp>. Such a subgraph groups its children without introducing a new
+// timestamp coordinate; the RootTimestamp coordinate carries no information, and times and
+// summaries convert back and forth without loss.
+pub struct RegionWrapper<T: Timestamp, S: Scope<T>> {
+    scope:      S,
+    internal:   Vec<CountMap<T>>,   // temp buffers for conversion of progress information
+    consumed:   Vec<CountMap<T>>,
+    produced:   Vec<CountMap<T>>,
+    external:   Vec<CountMap<T>>,
+}
+
+impl<T: Timestamp, S: Scope<T>> RegionWrapper<T, S> {
+    pub fn new(scope: S) -> RegionWrapper<T, S> {
+        let inputs = scope.inputs() as usize;
+        let outputs = scope.outputs() as usize;
+        RegionWrapper {
+            scope:      scope,
+            internal:   vec![CountMap::new(); outputs],
+            consumed:   vec![CountMap::new(); inputs],
+            produced:   vec![CountMap::new(); outputs],
+            external:   vec![CountMap::new(); inputs],
+        }
+    }
+}
+
+fn summary_in<S: Copy+Eq+Default>(summary: S) -> Summary<S, RootSummary> {
+    if summary == Default::default() { Local(RootSummary) } else { Outer(summary, RootSummary) }
+}
+
+fn summary_out<S: Copy+Default>(summary: Summary<S, RootSummary>) -> S {
+    match summary {
+        Local(_)    => Default::default(),
+        Outer(s, _) => s,
+    }
+}
+
+// moves updates from one count map to another, in product times.
+fn drain_in<T: Timestamp>(source: &mut CountMap<T>, target: &mut CountMap<Product<T, RootTimestamp>>) {
+    while let Some((time, delta)) = source.pop() {
+        target.update(&Product::new(time, RootTimestamp), delta);
+    }
+}
+
+// moves updates from one count map to another, in unwrapped times.
+fn drain_out<T: Timestamp>(source: &mut CountMap<Product<T, RootTimestamp>>, target: &mut CountMap<T>) {
+    while let Some((time, delta)) = source.pop() {
+        target.update(&time.outer, delta);
+    }
+}
+
+impl<T: Timestamp, S: Scope<T>> Scope<Product<T, RootTimestamp>> for RegionWrapper<T, S> {
+    fn inputs(&self) -> u64 { self.scope.inputs() }
+    fn outputs(&self) -> u64 { self.scope.outputs() }
+
+    fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<Summary<T::Summary, RootSummary>>>>,
+                                           Vec<CountMap<Product<T, RootTimestamp>>>) {
+        let (summaries, mut work) = self.scope.get_internal_summary();
+
+        let summaries = summaries.into_iter().map(|outputs| {
+            outputs.into_iter().map(|antichain| {
+                let mut result = Antichain::new();
+                for &summary in antichain.elements.iter() { result.insert(summary_in(summary)); }
+                result
+            }).collect()
+        }).collect();
+
+        let mut result = vec![CountMap::new(); work.len()];
+        for (output, counts) in work.iter_mut().enumerate() {
+            drain_in(counts, &mut result[output]);
+        }
+
+        (summaries, result)
+    }
+
+    fn set_external_summary(&mut self, summaries: Vec<Vec<Antichain<Summary<T::Summary, RootSummary>>>>,
+                                       frontier: &mut [CountMap<Product<T, RootTimestamp>>]) {
+        let summaries = summaries.into_iter().map(|inputs| {
+            inputs.into_iter().map(|antichain| {
+                let mut result = Antichain::new();
+                for &summary in antichain.elements.iter() { result.insert(summary_out(summary)); }
+                result
+            }).collect()
+        }).collect();
+
+        for (input, counts) in frontier.iter_mut().enumerate() {
+            drain_out(counts, &mut self.external[input]);
+        }
+
+        // scopes using the default set_external_summary leave the frontier unconsumed; as Subgraph
+        // does, we tolerate this and discard whatever remains.
+        self.scope.set_external_summary(summaries, &mut self.external[..]);
+        for changes in self.external.iter_mut() { changes.clear(); }
+    }
+
+    fn push_external_progress(&mut self, external: &mut [CountMap<Product<T, RootTimestamp>>]) {
+        for (input, counts) in external.iter_mut().enumerate() {
+            drain_out(counts, &mut self.external[input]);
+        }
+
+        self.scope.push_external_progress(&mut self.external[..]);
+        for changes in self.external.iter_mut() { changes.clear(); }
+    }
+
+    fn pull_internal_progress(&mut self, internal: &mut [CountMap<Product<T, RootTimestamp>>],
+                                         consumed: &mut [CountMap<Product<T, RootTimestamp>>],
+                                         produced: &mut [CountMap<Product<T, RootTimestamp>>]) -> bool {
+
+        let active = self.scope.pull_internal_progress(&mut self.internal[..], &mut self.consumed[..], &mut self.produced[..]);
+
+        for (output, counts) in self.internal.iter_mut().enumerate() { drain_in(counts, &mut internal[output]); }
+        for (input, counts) in self.consumed.iter_mut().enumerate() { drain_in(counts, &mut consumed[input]); }
+        for (output, counts) in self.produced.iter_mut().enumerate() { drain_in(counts, &mut produced[output]); }
+
+        active
+    }
+
+    fn name(&self) -> String { self.scope.name() }
+    fn notify_me(&self) -> bool { self.scope.notify_me() }
+}
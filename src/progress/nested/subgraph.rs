@@ -15,7 +15,7 @@ use progress::nested::Target::{GraphOutput, ScopeInput};
 use progress::nested::summary::Summary::{Local, Outer};
 use progress::count_map::CountMap;
 
-use progress::broadcast::{Progcaster, ProgressVec};
+use progress::broadcast::{Progcaster, ProgressVec, consolidate};
 use progress::nested::summary::Summary;
 use progress::nested::scope_wrapper::ScopeWrapper;
 use progress::nested::pointstamp_counter::PointstampCounter;
@@ -262,14 +262,16 @@ impl<TOuter: Timestamp, TInner: Timestamp> Scope<TOuter> for Subgraph<TOuter, TI
         }
 
         // Intermission: exchange pointstamp updates, and then move them to the pointstamps structure.
+        // updates are consolidated before they are sent, so that cancelled updates are not sent at
+        // all, and an idle subgraph sends nothing.
+        consolidate(&mut self.pointstamp_messages, &mut self.pointstamp_messages_cm);
+        consolidate(&mut self.pointstamp_internal, &mut self.pointstamp_internal_cm);
         self.progcaster.send_and_recv(&mut self.pointstamp_messages, &mut self.pointstamp_internal);
 
         {
-            // this aggregates down the pointstamps, removing any cancelled updates
-            while let Some((a, b, c, d)) = self.pointstamp_messages.pop() { self.pointstamp_messages_cm.update(&(a, b, c), d); }
-            while let Some(((a, b, c), d)) = self.pointstamp_messages_cm.pop() { self.pointstamp_messages.push((a, b, c, d)); }
-            while let Some((a, b, c, d)) = self.pointstamp_internal.pop() { self.pointstamp_internal_cm.update(&(a, b, c), d); }
-            while let Some(((a, b, c), d)) = self.pointstamp_internal_cm.pop() { self.pointstamp_internal.push((a, b, c, d)); }
+            // this aggregates down the pointstamps received from all workers, removing any cancelled updates
+            consolidate(&mut self.pointstamp_messages, &mut self.pointstamp_messages_cm);
+            consolidate(&mut self.pointstamp_internal, &mut self.pointstamp_internal_cm);
 
             let pointstamps = &mut self.pointstamps;    // clarify to Rust that we don't need &mut self for the closures.
             // println!("ps_msg: {:?}", self.pointstamp_messages);
@@ -13,6 +13,10 @@ use progress::count_map::CountMap;
 /// once there are no frontier elements less-or-equal to them, and there are no other pending
 /// notification requests less than them. Each with be less-or-equal to itself, so we want to
 /// dodge that corner case.
+///
+/// As a consequence, notifications requested through notify_at are delivered in time order: next
+/// never returns a time while a requested time less than it is still pending, so any such time is
+/// delivered first. Incomparable times are delivered in no particular order.
 
 #[derive(Default)]
 pub struct Notificator<T: Timestamp> {
@@ -20,6 +24,7 @@ pub struct Notificator<T: Timestamp> {
     frontier:       Vec<MutableAntichain<T>>,   // outstanding input, preventing notification
     available:      VecDeque<T>,                // notifications available for delivery
     changes:        CountMap<T>,                // change to report through pull_progress
+    scoped:         Vec<(Vec<usize>, MutableAntichain<T>)>, // requests blocked only by some inputs
 }
 
 impl<T: Timestamp> Notificator<T> {
@@ -39,6 +44,8 @@ impl<T: Timestamp> Notificator<T> {
         self.frontier[input].elements()
     }
 
+    pub fn inputs(&self) -> usize { self.frontier.len() }
+
     pub fn notify_at(&mut self, time: &T) {
         self.changes.update(time, 1);
         self.pending.update(time, 1);
@@ -51,6 +58,56 @@ impl<T: Timestamp> Notificator<T> {
         // }
     }
 
+    // requests a notification at time once the frontiers of the listed inputs have passed it.
+    // the request holds a capability just like notify_at, released when delivered by next_on.
+    pub fn notify_at_on(&mut self, time: &T, inputs: &[usize]) {
+        let inputs = self.normalize(inputs);
+        self.changes.update(time, 1);
+        if let Some(position) = self.scoped.iter().position(|x| x.0 == inputs) {
+            self.scoped[position].1.update(time, 1);
+        }
+        else {
+            let mut pending = MutableAntichain::new();
+            pending.update(time, 1);
+            self.scoped.push((inputs, pending));
+        }
+    }
+
+    // returns a notification requested through notify_at_on with the same list of inputs, if any
+    // such notification is no longer blocked by the frontiers of those inputs.
+    pub fn next_on(&mut self, inputs: &[usize]) -> Option<(T, i64)> {
+        let inputs = self.normalize(inputs);
+        if let Some(position) = self.scoped.iter().position(|x| x.0 == inputs) {
+            let available = {
+                let frontier = &self.frontier;
+                self.scoped[position].1.elements().iter()
+                    .find(|pend| !inputs.iter().any(|&input| frontier[input].le(pend)))
+                    .map(|pend| pend.clone())
+            };
+
+            if let Some(time) = available {
+                if let Some(delta) = self.scoped[position].1.count(&time) {
+                    self.changes.update(&time, -delta);
+                    self.scoped[position].1.update(&time, -delta);
+                    return Some((time, delta));
+                }
+            }
+        }
+
+        None
+    }
+
+    // sorts and dedups a list of inputs, so that equivalent lists name the same requests.
+    fn normalize(&self, inputs: &[usize]) -> Vec<usize> {
+        for &input in inputs.iter() {
+            assert!(input < self.frontier.len(), "Notificator: input {} out of range; only {} inputs", input, self.frontier.len());
+        }
+        let mut inputs = inputs.to_vec();
+        inputs.sort();
+        inputs.dedup();
+        inputs
+    }
+
     pub fn pull_progress(&mut self, internal: &mut CountMap<T>) {
         while let Some((time, delta)) = self.changes.pop() {
             internal.update(&time, delta);
@@ -78,15 +135,18 @@ impl<T: Timestamp> Iterator for Notificator<T> {
         //     println!("  pending:  {:?}", self.pending);
         // }
 
-        // return an available notification, after cleaning up
-        if let Some(time) = self.available.pop_front() {
-            if let Some(delta) = self.pending.count(&time) {
-                self.changes.update(&time, -delta);
-                self.pending.update(&time, -delta);
-                Some((time, delta))
+        // return an available notification, after cleaning up. times for which a smaller request
+        // has arrived since they became available are skipped, and found again by a later scan.
+        while let Some(time) = self.available.pop_front() {
+            if !self.pending.lt(&time) {
+                if let Some(delta) = self.pending.count(&time) {
+                    self.changes.update(&time, -delta);
+                    self.pending.update(&time, -delta);
+                    return Some((time, delta));
+                }
             }
-            else { None }
         }
-        else { None }
+
+        None
     }
 }
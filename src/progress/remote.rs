@@ -0,0 +1,334 @@
+use std::io::{Read, Write, Error, ErrorKind};
+use std::io::Result as IoResult;
+use std::marker::PhantomData;
+use std::fmt::Debug;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LittleEndian};
+
+use progress::{Timestamp, Scope, CountMap, Antichain};
+use progress::timestamp::{RootTimestamp, RootSummary};
+use progress::nested::product::Product;
+use progress::nested::summary::Summary;
+use progress::nested::summary::Summary::{Local, Outer};
+
+// A Scope may be hosted outside the process that schedules it, as it only exchanges summaries and
+// CountMaps with its parent. RemoteScope forwards each Scope method over a byte stream to a host
+// running serve_scope, and returns the host's responses. All integers are little-endian.
+//
+// On connection, the host sends a handshake: u64 MAGIC, u64 VERSION, u64 inputs, u64 outputs, u8
+// notify_me, and the scope's name as a u64 length and UTF-8 bytes. Each request is then an opcode
+// byte and a body, answered synchronously:
+//
+//   0 (get_internal_summary):   no body; answered with the summaries, then outputs CountMaps.
+//   1 (set_external_summary):   the summaries, then inputs CountMaps; no answer.
+//   2 (push_external_progress): inputs CountMaps; no answer.
+//   3 (pull_internal_progress): no body; answered with outputs CountMaps of internal changes,
+//                               inputs CountMaps of consumed messages, outputs CountMaps of
+//                               produced messages, and a u8 that is 1 if work remains.
+//
+// Summaries are a u64 number of rows, each a u64 number of antichains, each a u64 number of
+// elements and those elements. A CountMap is a u64 number of entries, each a time and an i64.
+// Times and summaries are encoded by Codec, with fixed-width fields in the order declared:
+//
+//   u64, u32, i32:              8, 4 and 4 bytes.
+//   RootTimestamp, RootSummary: no bytes.
+//   Product(outer, inner):      outer, then inner.
+//   Summary:                    a u8 tag, 0 for Local(inner), followed by inner, or 1 for
+//                               Outer(outer, inner), followed by outer and then inner.
+//
+// for example, a Product<RootTimestamp, u64> is 8 bytes, and each of its summaries 9 bytes.
+
+const MAGIC: u64 = 0x5345_504f_4353_4454;   // "TDSCOPES"
+const VERSION: u64 = 1;
+
+const GET_INTERNAL_SUMMARY: u8 = 0;
+const SET_EXTERNAL_SUMMARY: u8 = 1;
+const PUSH_EXTERNAL_PROGRESS: u8 = 2;
+const PULL_INTERNAL_PROGRESS: u8 = 3;
+
+// a fixed binary encoding, for timestamps and summaries exchanged with a remote scope.
+pub trait Codec: Sized {
+    fn encode<W: Write>(&self, writer: &mut W) -> IoResult<()>;
+    fn decode<R: Read>(reader: &mut R) -> IoResult<Self>;
+}
+
+impl Codec for u64 {
+    fn encode<W: Write>(&self, writer: &mut W) -> IoResult<()> { Ok(try!(writer.write_u64::<LittleEndian>(*self))) }
+    fn decode<R: Read>(reader: &mut R) -> IoResult<u64> { Ok(try!(reader.read_u64::<LittleEndian>())) }
+}
+
+impl Codec for u32 {
+    fn encode<W: Write>(&self, writer: &mut W) -> IoResult<()> { Ok(try!(writer.write_u32::<LittleEndian>(*self))) }
+    fn decode<R: Read>(reader: &mut R) -> IoResult<u32> { Ok(try!(reader.read_u32::<LittleEndian>())) }
+}
+
+impl Codec for i32 {
+    fn encode<W: Write>(&self, writer: &mut W) -> IoResult<()> { Ok(try!(writer.write_i32::<LittleEndian>(*self))) }
+    fn decode<R: Read>(reader: &mut R) -> IoResult<i32> { Ok(try!(reader.read_i32::<LittleEndian>())) }
+}
+
+// root timestamps and summaries carry no information, and are encoded as nothing.
+impl Codec for RootTimestamp {
+    fn encode<W: Write>(&self, _writer: &mut W) -> IoResult<()> { Ok(()) }
+    fn decode<R: Read>(_reader: &mut R) -> IoResult<RootTimestamp> { Ok(RootTimestamp) }
+}
+
+impl Codec for RootSummary {
+    fn encode<W: Write>(&self, _writer: &mut W) -> IoResult<()> { Ok(()) }
+    fn decode<R: Read>(_reader: &mut R) -> IoResult<RootSummary> { Ok(RootSummary) }
+}
+
+impl<TOuter: Codec, TInner: Codec> Codec for Product<TOuter, TInner> {
+    fn encode<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        try!(self.outer.encode(writer));
+        self.inner.encode(writer)
+    }
+    fn decode<R: Read>(reader: &mut R) -> IoResult<Product<TOuter, TInner>> {
+        let outer = try!(Codec::decode(reader));
+        let inner = try!(Codec::decode(reader));
+        Ok(Product::new(outer, inner))
+    }
+}
+
+// a tag byte, 0 for Local and 1 for Outer, followed by the summary's fields.
+impl<S: Codec, T: Codec> Codec for Summary<S, T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        match *self {
+            Local(ref inner) => {
+                try!(writer.write_u8(0));
+                inner.encode(writer)
+            },
+            Outer(ref outer, ref inner) => {
+                try!(writer.write_u8(1));
+                try!(outer.encode(writer));
+                inner.encode(writer)
+            },
+        }
+    }
+    fn decode<R: Read>(reader: &mut R) -> IoResult<Summary<S, T>> {
+        match try!(reader.read_u8()) {
+            0 => Ok(Local(try!(Codec::decode(reader)))),
+            1 => {
+                let outer = try!(Codec::decode(reader));
+                let inner = try!(Codec::decode(reader));
+                Ok(Outer(outer, inner))
+            },
+            _ => Err(Error::new(ErrorKind::InvalidInput, "Codec: invalid summary tag")),
+        }
+    }
+}
+
+// proxies a scope hosted by serve_scope at the other end of reader and writer. i/o errors are
+// fatal, as the Scope methods have no way to report them.
+pub struct RemoteScope<T: Timestamp, R: Read, W: Write> {
+    reader:     R,
+    writer:     W,
+    name:       String,
+    inputs:     u64,
+    outputs:    u64,
+    notify:     bool,
+    phantom:    PhantomData<T>,
+}
+
+impl<T: Timestamp+Codec, R: Read, W: Write> RemoteScope<T, R, W> where T::Summary: Codec {
+    pub fn new(mut reader: R, writer: W) -> IoResult<RemoteScope<T, R, W>> {
+        let magic = try!(reader.read_u64::<LittleEndian>());
+        let version = try!(reader.read_u64::<LittleEndian>());
+        if magic != MAGIC {
+            return Err(Error::new(ErrorKind::InvalidInput, "RemoteScope: not a scope host"));
+        }
+        if version != VERSION {
+            return Err(Error::new(ErrorKind::InvalidInput, "RemoteScope: unsupported version"));
+        }
+
+        let inputs = try!(reader.read_u64::<LittleEndian>());
+        let outputs = try!(reader.read_u64::<LittleEndian>());
+        let notify = try!(reader.read_u8()) != 0;
+        let length = try!(reader.read_u64::<LittleEndian>()) as usize;
+        let mut bytes = vec![0u8; length];
+        try!(read_fully(&mut reader, &mut bytes[..]));
+        let name = match String::from_utf8(bytes) {
+            Ok(name) => name,
+            Err(_)   => return Err(Error::new(ErrorKind::InvalidInput, "RemoteScope: invalid name")),
+        };
+
+        Ok(RemoteScope {
+            reader:     reader,
+            writer:     writer,
+            name:       name,
+            inputs:     inputs,
+            outputs:    outputs,
+            notify:     notify,
+            phantom:    PhantomData,
+        })
+    }
+}
+
+impl<T: Timestamp+Codec, R: Read, W: Write> Scope<T> for RemoteScope<T, R, W> where T::Summary: Codec {
+    fn name(&self) -> String { self.name.clone() }
+    fn inputs(&self) -> u64 { self.inputs }
+    fn outputs(&self) -> u64 { self.outputs }
+
+    fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<T::Summary>>>, Vec<CountMap<T>>) {
+        self.writer.write_u8(GET_INTERNAL_SUMMARY).ok().expect("RemoteScope: write error");
+        self.writer.flush().ok().expect("RemoteScope: flush error");
+
+        let summaries = read_summaries(&mut self.reader).ok().expect("RemoteScope: read error");
+        let mut capabilities = vec![CountMap::new(); self.outputs as usize];
+        read_counts(&mut self.reader, &mut capabilities[..]).ok().expect("RemoteScope: read error");
+        (summaries, capabilities)
+    }
+
+    fn set_external_summary(&mut self, summaries: Vec<Vec<Antichain<T::Summary>>>, frontier: &mut [CountMap<T>]) {
+        self.writer.write_u8(SET_EXTERNAL_SUMMARY).ok().expect("RemoteScope: write error");
+        write_summaries(&mut self.writer, &summaries).ok().expect("RemoteScope: write error");
+        write_counts(&mut self.writer, frontier).ok().expect("RemoteScope: write error");
+        self.writer.flush().ok().expect("RemoteScope: flush error");
+    }
+
+    fn push_external_progress(&mut self, external: &mut [CountMap<T>]) {
+        self.writer.write_u8(PUSH_EXTERNAL_PROGRESS).ok().expect("RemoteScope: write error");
+        write_counts(&mut self.writer, external).ok().expect("RemoteScope: write error");
+        self.writer.flush().ok().expect("RemoteScope: flush error");
+    }
+
+    fn pull_internal_progress(&mut self, internal: &mut [CountMap<T>],
+                                         consumed: &mut [CountMap<T>],
+                                         produced: &mut [CountMap<T>]) -> bool
+    {
+        self.writer.write_u8(PULL_INTERNAL_PROGRESS).ok().expect("RemoteScope: write error");
+        self.writer.flush().ok().expect("RemoteScope: flush error");
+
+        read_counts(&mut self.reader, internal).ok().expect("RemoteScope: read error");
+        read_counts(&mut self.reader, consumed).ok().expect("RemoteScope: read error");
+        read_counts(&mut self.reader, produced).ok().expect("RemoteScope: read error");
+        self.reader.read_u8().ok().expect("RemoteScope: read error") != 0
+    }
+
+    fn notify_me(&self) -> bool { self.notify }
+}
+
+// hosts scope for a RemoteScope at the other end of reader and writer, answering requests until
+// the reader ends.
+pub fn serve_scope<T, S, R, W>(scope: &mut S, mut reader: R, mut writer: W) -> IoResult<()>
+where T: Timestamp+Codec, T::Summary: Codec, S: Scope<T>, R: Read, W: Write {
+
+    let inputs = scope.inputs() as usize;
+    let outputs = scope.outputs() as usize;
+    let name = scope.name();
+
+    try!(writer.write_u64::<LittleEndian>(MAGIC));
+    try!(writer.write_u64::<LittleEndian>(VERSION));
+    try!(writer.write_u64::<LittleEndian>(inputs as u64));
+    try!(writer.write_u64::<LittleEndian>(outputs as u64));
+    try!(writer.write_u8(if scope.notify_me() { 1 } else { 0 }));
+    try!(writer.write_u64::<LittleEndian>(name.len() as u64));
+    try!(writer.write_all(name.as_bytes()));
+    try!(writer.flush());
+
+    loop {
+        let opcode = match reader.read_u8() {
+            Ok(opcode) => opcode,
+            Err(_)     => return Ok(()),    // the client has gone away
+        };
+
+        match opcode {
+            GET_INTERNAL_SUMMARY => {
+                let (summaries, mut capabilities) = scope.get_internal_summary();
+                try!(write_summaries(&mut writer, &summaries));
+                try!(write_counts(&mut writer, &mut capabilities[..]));
+            },
+            SET_EXTERNAL_SUMMARY => {
+                let summaries = try!(read_summaries(&mut reader));
+                let mut frontier = vec![CountMap::new(); inputs];
+                try!(read_counts(&mut reader, &mut frontier[..]));
+                scope.set_external_summary(summaries, &mut frontier[..]);
+            },
+            PUSH_EXTERNAL_PROGRESS => {
+                let mut external = vec![CountMap::new(); inputs];
+                try!(read_counts(&mut reader, &mut external[..]));
+                scope.push_external_progress(&mut external[..]);
+            },
+            PULL_INTERNAL_PROGRESS => {
+                let mut internal = vec![CountMap::new(); outputs];
+                let mut consumed = vec![CountMap::new(); inputs];
+                let mut produced = vec![CountMap::new(); outputs];
+                let active = scope.pull_internal_progress(&mut internal[..], &mut consumed[..], &mut produced[..]);
+                try!(write_counts(&mut writer, &mut internal[..]));
+                try!(write_counts(&mut writer, &mut consumed[..]));
+                try!(write_counts(&mut writer, &mut produced[..]));
+                try!(writer.write_u8(if active { 1 } else { 0 }));
+            },
+            _ => return Err(Error::new(ErrorKind::InvalidInput, "serve_scope: unknown opcode")),
+        }
+
+        try!(writer.flush());
+    }
+}
+
+fn write_summaries<S: Codec, W: Write>(writer: &mut W, summaries: &Vec<Vec<Antichain<S>>>) -> IoResult<()> {
+    try!(writer.write_u64::<LittleEndian>(summaries.len() as u64));
+    for row in summaries.iter() {
+        try!(writer.write_u64::<LittleEndian>(row.len() as u64));
+        for antichain in row.iter() {
+            try!(writer.write_u64::<LittleEndian>(antichain.elements.len() as u64));
+            for summary in antichain.elements.iter() {
+                try!(summary.encode(writer));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_summaries<S: Codec+PartialOrd+Eq+Copy+Debug, R: Read>(reader: &mut R) -> IoResult<Vec<Vec<Antichain<S>>>> {
+    let mut summaries = Vec::new();
+    for _ in (0..try!(reader.read_u64::<LittleEndian>())) {
+        let mut row = Vec::new();
+        for _ in (0..try!(reader.read_u64::<LittleEndian>())) {
+            let mut antichain = Antichain::new();
+            for _ in (0..try!(reader.read_u64::<LittleEndian>())) {
+                antichain.elements.push(try!(Codec::decode(reader)));
+            }
+            row.push(antichain);
+        }
+        summaries.push(row);
+    }
+    Ok(summaries)
+}
+
+// writes and consumes the contents of each CountMap.
+fn write_counts<T: Timestamp+Codec, W: Write>(writer: &mut W, counts: &mut [CountMap<T>]) -> IoResult<()> {
+    for count in counts.iter_mut() {
+        try!(writer.write_u64::<LittleEndian>(count.len() as u64));
+        while let Some((time, delta)) = count.pop() {
+            try!(time.encode(writer));
+            try!(writer.write_i64::<LittleEndian>(delta));
+        }
+    }
+    Ok(())
+}
+
+// reads updates into each CountMap.
+fn read_counts<T: Timestamp+Codec, R: Read>(reader: &mut R, counts: &mut [CountMap<T>]) -> IoResult<()> {
+    for count in counts.iter_mut() {
+        for _ in (0..try!(reader.read_u64::<LittleEndian>())) {
+            let time: T = try!(Codec::decode(reader));
+            let delta = try!(reader.read_i64::<LittleEndian>());
+            count.update(&time, delta);
+        }
+    }
+    Ok(())
+}
+
+fn read_fully<R: Read>(reader: &mut R, buffer: &mut [u8]) -> IoResult<()> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0)       => return Err(Error::new(ErrorKind::InvalidInput, "unexpected end of stream")),
+            Ok(read)    => filled += read,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => { },
+            Err(e)      => return Err(e),
+        }
+    }
+    Ok(())
+}